@@ -16,8 +16,10 @@ use tracing::debug;
 use zbus::zvariant::ObjectPath;
 
 use crate::config::Config;
+use crate::dbus::properties::GetAllTimings;
+use crate::dbus::properties::Properties;
+use crate::dbus::properties::UNIT_INTERFACE;
 use crate::dbus::zbus_systemd::ManagerProxy;
-use crate::dbus::zbus_unit::UnitProxy;
 use crate::MachineStats;
 
 /// Boot blame statistics: maps unit name to activation time in seconds
@@ -26,10 +28,10 @@ pub type BootBlameStats = HashMap<String, f64>;
 const BOOT_ID_PATH: &str = "/proc/sys/kernel/random/boot_id";
 const BOOT_BLAME_CACHE_SUFFIX: &str = "boot_blame.bin";
 
-type BootCacheResult<T> = std::result::Result<T, BootCacheError>;
+pub(crate) type BootCacheResult<T> = std::result::Result<T, BootCacheError>;
 
 #[derive(Debug, thiserror::Error)]
-enum BootCacheError {
+pub(crate) enum BootCacheError {
     #[error("boot cache I/O error: {0}")]
     Io(#[from] std::io::Error),
     #[error("boot id from {BOOT_ID_PATH} was empty")]
@@ -42,13 +44,20 @@ enum BootCacheError {
     IntConversion(#[from] TryFromIntError),
     #[error("boot cache slice conversion error: {0}")]
     SliceConversion(#[from] TryFromSliceError),
+    #[error("boot cache JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Per boot cache files are named `<boot_id>.<suffix>` in `cache_dir`
+pub(crate) fn boot_cache_file_path(cache_dir: &Path, boot_id: &str, suffix: &str) -> PathBuf {
+    cache_dir.join(format!("{boot_id}.{suffix}"))
 }
 
 fn cache_file_path(cache_dir: &Path, boot_id: &str) -> PathBuf {
-    cache_dir.join(format!("{boot_id}.{BOOT_BLAME_CACHE_SUFFIX}"))
+    boot_cache_file_path(cache_dir, boot_id, BOOT_BLAME_CACHE_SUFFIX)
 }
 
-async fn get_boot_id() -> BootCacheResult<String> {
+pub(crate) async fn get_boot_id() -> BootCacheResult<String> {
     let boot_id = tokio::fs::read_to_string(BOOT_ID_PATH).await?;
     let boot_id = boot_id.trim().to_string();
     if boot_id.is_empty() {
@@ -143,15 +152,12 @@ async fn write_cached_boot_blame_to_dir(
 async fn get_unit_activation_time(
     connection: &zbus::Connection,
     unit_path: &ObjectPath<'_>,
+    get_all_timings: &mut GetAllTimings,
 ) -> Result<f64> {
-    let unit_proxy = UnitProxy::builder(connection)
-        .cache_properties(zbus::proxy::CacheProperties::No)
-        .path(unit_path)?
-        .build()
-        .await?;
-
-    let inactive_exit = unit_proxy.inactive_exit_timestamp().await?;
-    let active_enter = unit_proxy.active_enter_timestamp().await?;
+    let unit = Properties::get_all(connection, unit_path, UNIT_INTERFACE).await?;
+    get_all_timings.record(&unit);
+    let inactive_exit: u64 = unit.get("InactiveExitTimestamp")?;
+    let active_enter: u64 = unit.get("ActiveEnterTimestamp")?;
 
     // If either timestamp is 0, the unit hasn't been activated or the timing is invalid
     if inactive_exit == 0 || active_enter == 0 {
@@ -165,11 +171,61 @@ async fn get_unit_activation_time(
     Ok(activation_time_sec)
 }
 
-/// Update boot blame statistics with the N slowest units at boot
+/// Activation times in seconds of every activated unit, filtered by the
+/// `[boot]` allowlist and blocklist
+pub(crate) async fn get_unit_activation_times(
+    config: &Config,
+    connection: &zbus::Connection,
+    get_all_timings: &mut GetAllTimings,
+) -> Result<Vec<(String, f64)>> {
+    let systemd_proxy = ManagerProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let units = systemd_proxy.list_units().await?;
+
+    let mut unit_times: Vec<(String, f64)> = Vec::new();
+
+    // Collect activation times for all units
+    for unit_info in units {
+        let unit_name = unit_info.0;
+        let unit_path = unit_info.6;
+
+        // Apply blocklist: skip units explicitly excluded
+        if config.boot_blame.blocklist.contains(&unit_name) {
+            debug!("Skipping boot blame for {} due to blocklist", &unit_name);
+            continue;
+        }
+        // Apply allowlist: if non-empty, only include listed units
+        if !config.boot_blame.allowlist.is_empty()
+            && !config.boot_blame.allowlist.contains(&unit_name)
+        {
+            continue;
+        }
+
+        match get_unit_activation_time(connection, &unit_path, get_all_timings).await {
+            Ok(time) if time > 0.0 => {
+                unit_times.push((unit_name, time));
+            }
+            Ok(_) => {
+                // Unit has no activation time (0.0), skip it
+            }
+            Err(e) => {
+                debug!("Failed to get activation time for {}: {}", unit_name, e);
+            }
+        }
+    }
+
+    Ok(unit_times)
+}
+
+/// Update boot blame statistics with the N slowest units at boot.
+/// The `GetAll` calls made are added to `get_all_timings`.
 pub async fn update_boot_blame_stats(
     config: Arc<Config>,
     connection: zbus::Connection,
     machine_stats: Arc<RwLock<MachineStats>>,
+    get_all_timings: Arc<RwLock<GetAllTimings>>,
 ) -> Result<()> {
     debug!("Starting boot blame stats collection");
 
@@ -212,43 +268,10 @@ pub async fn update_boot_blame_stats(
         }
     }
 
-    let systemd_proxy = ManagerProxy::builder(&connection)
-        .cache_properties(zbus::proxy::CacheProperties::No)
-        .build()
-        .await?;
-    let units = systemd_proxy.list_units().await?;
-
-    let mut unit_times: Vec<(String, f64)> = Vec::new();
-
-    // Collect activation times for all units
-    for unit_info in units {
-        let unit_name = unit_info.0;
-        let unit_path = unit_info.6;
-
-        // Apply blocklist: skip units explicitly excluded
-        if config.boot_blame.blocklist.contains(&unit_name) {
-            debug!("Skipping boot blame for {} due to blocklist", &unit_name);
-            continue;
-        }
-        // Apply allowlist: if non-empty, only include listed units
-        if !config.boot_blame.allowlist.is_empty()
-            && !config.boot_blame.allowlist.contains(&unit_name)
-        {
-            continue;
-        }
-
-        match get_unit_activation_time(&connection, &unit_path).await {
-            Ok(time) if time > 0.0 => {
-                unit_times.push((unit_name, time));
-            }
-            Ok(_) => {
-                // Unit has no activation time (0.0), skip it
-            }
-            Err(e) => {
-                debug!("Failed to get activation time for {}: {}", unit_name, e);
-            }
-        }
-    }
+    let mut timings = GetAllTimings::default();
+    let unit_times = get_unit_activation_times(&config, &connection, &mut timings).await;
+    get_all_timings.write().await.add(&timings);
+    let mut unit_times = unit_times?;
 
     // Sort by activation time in descending order (slowest first)
     unit_times.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
@@ -0,0 +1,331 @@
+//! # boot_critical_chain module
+//!
+//! Collects the chain of units that gated reaching a target at boot, the
+//! same as `systemd-analyze critical-chain`. Starting at the target, follow
+//! the `After=` dependency that became active last until a unit with no
+//! such dependency is reached. Only ordering can delay a unit, so units
+//! pulled in by `Wants=` / `Requires=` without `After=` are not followed.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::boot::boot_cache_file_path;
+use crate::boot::get_boot_id;
+use crate::boot::BootCacheResult;
+use crate::config::Config;
+use crate::dbus::zbus_systemd::ManagerProxy;
+use crate::dbus::zbus_unit::UnitProxy;
+use crate::MachineStats;
+
+const BOOT_CRITICAL_CHAIN_CACHE_SUFFIX: &str = "boot_critical_chain.json";
+
+/// One unit on the critical chain
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct CriticalChainHop {
+    pub unit: String,
+    /// Seconds after userspace started that the unit became active
+    pub activated_secs: f64,
+    /// Seconds the unit took to activate (0 for targets and instant units)
+    pub activation_secs: f64,
+}
+
+/// The critical chain for a target, from the target back to the first unit
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BootCriticalChainStats {
+    pub target: String,
+    pub chain: Vec<CriticalChainHop>,
+}
+
+/// Unit `*TimestampMonotonic` values in microseconds
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct UnitTimes {
+    activating: u64,
+    activated: u64,
+}
+
+fn usec_to_secs(usec: u64) -> f64 {
+    usec as f64 / 1_000_000.0
+}
+
+fn make_hop(unit: &str, times: UnitTimes, userspace: u64) -> CriticalChainHop {
+    CriticalChainHop {
+        unit: unit.to_string(),
+        activated_secs: usec_to_secs(times.activated.saturating_sub(userspace)),
+        activation_secs: match times.activating > 0 {
+            true => usec_to_secs(times.activated.saturating_sub(times.activating)),
+            false => 0.0,
+        },
+    }
+}
+
+/// The dependency that became active last during boot. Units activated
+/// after startup finished (e.g. restarted since) are ignored.
+fn pick_next_hop(deps: &[(String, UnitTimes)], finish: u64) -> Option<&(String, UnitTimes)> {
+    deps.iter()
+        .filter(|(_, times)| times.activated > 0 && times.activated <= finish)
+        .max_by(|a, b| a.1.activated.cmp(&b.1.activated).then(b.0.cmp(&a.0)))
+}
+
+async fn get_unit_times_and_after(
+    connection: &zbus::Connection,
+    manager: &ManagerProxy<'_>,
+    unit: &str,
+) -> zbus::Result<(UnitTimes, Vec<String>)> {
+    let unit_path = manager.get_unit(unit).await?;
+    let unit_proxy = UnitProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .path(unit_path)?
+        .build()
+        .await?;
+    let times = UnitTimes {
+        activating: unit_proxy.inactive_exit_timestamp_monotonic().await?,
+        activated: unit_proxy.active_enter_timestamp_monotonic().await?,
+    };
+    Ok((times, unit_proxy.after().await?))
+}
+
+/// Walk `After=` from `target`. Returns None if startup has not finished.
+async fn get_critical_chain(
+    connection: &zbus::Connection,
+    target: &str,
+) -> Result<Option<BootCriticalChainStats>> {
+    let manager = ManagerProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let finish = manager.finish_timestamp_monotonic().await?;
+    if finish == 0 {
+        return Ok(None);
+    }
+    let userspace = manager.userspace_timestamp_monotonic().await?;
+
+    let mut stats = BootCriticalChainStats {
+        target: target.to_string(),
+        chain: Vec::new(),
+    };
+    let mut visited = HashSet::from([target.to_string()]);
+    let (mut times, mut after) = get_unit_times_and_after(connection, &manager, target).await?;
+    let mut unit = target.to_string();
+    loop {
+        stats.chain.push(make_hop(&unit, times, userspace));
+
+        let mut deps = Vec::with_capacity(after.len());
+        let mut deps_after = HashMap::new();
+        for dep in after {
+            if visited.contains(&dep) {
+                continue;
+            }
+            match get_unit_times_and_after(connection, &manager, &dep).await {
+                Ok((dep_times, dep_after)) => {
+                    deps_after.insert(dep.clone(), dep_after);
+                    deps.push((dep, dep_times));
+                }
+                // Not loaded units never ran so can't be on the chain
+                Err(err) => debug!("Skipping {} for critical chain: {}", dep, err),
+            }
+        }
+        let Some((next_unit, next_times)) = pick_next_hop(&deps, finish).cloned() else {
+            break;
+        };
+        visited.insert(next_unit.clone());
+        after = deps_after.remove(&next_unit).unwrap_or_default();
+        times = next_times;
+        unit = next_unit;
+    }
+    Ok(Some(stats))
+}
+
+async fn read_cached_critical_chain_from_dir(
+    cache_dir: &Path,
+    boot_id: &str,
+) -> BootCacheResult<Option<BootCriticalChainStats>> {
+    let cache_path = boot_cache_file_path(cache_dir, boot_id, BOOT_CRITICAL_CHAIN_CACHE_SUFFIX);
+    let content = match tokio::fs::read(&cache_path).await {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+async fn write_cached_critical_chain_to_dir(
+    cache_dir: &Path,
+    boot_id: &str,
+    stats: &BootCriticalChainStats,
+) -> BootCacheResult<()> {
+    tokio::fs::create_dir_all(cache_dir).await?;
+    let cache_path = boot_cache_file_path(cache_dir, boot_id, BOOT_CRITICAL_CHAIN_CACHE_SUFFIX);
+    tokio::fs::write(cache_path, serde_json::to_vec(stats)?).await?;
+    Ok(())
+}
+
+/// Update the boot critical chain. The chain can't change once startup has
+/// finished so it is cached in memory and, with `[boot] cache_enabled`, per
+/// boot id in `[boot] cache_dir` like boot blame.
+pub async fn update_boot_critical_chain_stats(
+    config: Arc<Config>,
+    connection: zbus::Connection,
+    machine_stats: Arc<RwLock<MachineStats>>,
+) -> Result<()> {
+    let target = &config.boot_critical_chain.target;
+    if machine_stats
+        .read()
+        .await
+        .boot_critical_chain
+        .as_ref()
+        .is_some_and(|chain| &chain.target == target)
+    {
+        debug!("Using in-memory cached boot critical chain");
+        return Ok(());
+    }
+
+    let cache_dir = Path::new(&config.boot_blame.cache_dir);
+    let mut maybe_boot_id = None;
+    if config.boot_blame.cache_enabled {
+        match get_boot_id().await {
+            Ok(boot_id) => {
+                match read_cached_critical_chain_from_dir(cache_dir, &boot_id).await {
+                    Ok(Some(cached_chain)) if &cached_chain.target == target => {
+                        debug!("Using cached boot critical chain for boot id {}", boot_id);
+                        machine_stats.write().await.boot_critical_chain = Some(cached_chain);
+                        return Ok(());
+                    }
+                    Ok(_) => {
+                        debug!(
+                            "No cached boot critical chain for {} and boot id {}",
+                            target, boot_id
+                        );
+                    }
+                    Err(err) => {
+                        debug!(
+                            "Failed to load boot critical chain cache for boot id {}: {}",
+                            boot_id, err
+                        );
+                    }
+                }
+                maybe_boot_id = Some(boot_id);
+            }
+            Err(err) => {
+                debug!(
+                    "Failed to retrieve boot id for boot critical chain cache: {}",
+                    err
+                );
+            }
+        }
+    }
+
+    let Some(critical_chain) = get_critical_chain(&connection, target).await? else {
+        debug!("Startup has not finished. Skipping boot critical chain");
+        return Ok(());
+    };
+    debug!(
+        "Collected boot critical chain of {} units for {}",
+        critical_chain.chain.len(),
+        target
+    );
+    if let Some(boot_id) = maybe_boot_id {
+        if let Err(err) =
+            write_cached_critical_chain_to_dir(cache_dir, &boot_id, &critical_chain).await
+        {
+            debug!(
+                "Failed to write boot critical chain cache for boot id {} to {}: {}",
+                boot_id,
+                cache_dir.display(),
+                err
+            );
+        }
+    }
+    machine_stats.write().await.boot_critical_chain = Some(critical_chain);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_next_hop() {
+        let deps = vec![
+            (
+                String::from("early.service"),
+                UnitTimes {
+                    activating: 1_000_000,
+                    activated: 2_000_000,
+                },
+            ),
+            (
+                String::from("late.service"),
+                UnitTimes {
+                    activating: 2_000_000,
+                    activated: 5_000_000,
+                },
+            ),
+            (
+                String::from("restarted.service"),
+                UnitTimes {
+                    activating: 90_000_000,
+                    activated: 99_000_000,
+                },
+            ),
+            (String::from("never.service"), UnitTimes::default()),
+        ];
+        assert_eq!(
+            Some(&deps[1]),
+            pick_next_hop(&deps, 10_000_000),
+            "Should pick the last unit active before startup finished"
+        );
+        assert_eq!(None, pick_next_hop(&deps[3..], 10_000_000));
+    }
+
+    #[test]
+    fn test_make_hop() {
+        let times = UnitTimes {
+            activating: 3_000_000,
+            activated: 4_500_000,
+        };
+        assert_eq!(
+            CriticalChainHop {
+                unit: String::from("foo.service"),
+                activated_secs: 3.5,
+                activation_secs: 1.5,
+            },
+            make_hop("foo.service", times, 1_000_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_critical_chain_cache_read_write_roundtrip() {
+        let temp_dir = tempfile::tempdir().expect("create temp dir");
+        let boot_id = "00000000-0000-0000-0000-000000000003";
+        assert!(
+            read_cached_critical_chain_from_dir(temp_dir.path(), boot_id)
+                .await
+                .expect("missing cache should not error")
+                .is_none()
+        );
+        let stats = BootCriticalChainStats {
+            target: String::from("default.target"),
+            chain: vec![CriticalChainHop {
+                unit: String::from("default.target"),
+                activated_secs: 4.2,
+                activation_secs: 0.0,
+            }],
+        };
+        write_cached_critical_chain_to_dir(temp_dir.path(), boot_id, &stats)
+            .await
+            .expect("write cache");
+        assert_eq!(
+            Some(stats),
+            read_cached_critical_chain_from_dir(temp_dir.path(), boot_id)
+                .await
+                .expect("read cache")
+        );
+    }
+}
@@ -0,0 +1,328 @@
+//! # boot_history module
+//!
+//! Keeps a bounded history of past boots in `<history_dir>/boot_history.json`
+//! and compares this boot against the median of the previous boots, so boot
+//! time regressions show up without keeping every boot in a metrics store.
+//! A unit has regressed when its activation time is more than
+//! `regression_threshold_percent` above its median.
+//!
+//! `history_dir` defaults to `/var/lib/monitord` rather than the `[boot]`
+//! `cache_dir`, as `/run` is emptied at every reboot.
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Result;
+use tokio::sync::RwLock;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+use crate::boot::get_boot_id;
+use crate::boot::get_unit_activation_times;
+use crate::config::Config;
+use crate::dbus::properties::GetAllTimings;
+use crate::MachineStats;
+
+const BOOT_HISTORY_FILE: &str = "boot_history.json";
+
+/// One boot as stored in the history file
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BootRecord {
+    pub boot_id: String,
+    /// Unix time monitord recorded the boot
+    pub timestamp: u64,
+    /// Firmware to startup finished in seconds
+    pub total_secs: f64,
+    /// Unit name to activation time in seconds
+    pub unit_activation_secs: HashMap<String, f64>,
+}
+
+/// A value from this boot compared to the median of previous boots
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BootHistoryComparison {
+    pub secs: f64,
+    pub median_secs: f64,
+    /// secs - median_secs
+    pub delta_secs: f64,
+    pub regressed: bool,
+}
+
+impl BootHistoryComparison {
+    fn new(secs: f64, previous: &[f64], regression_threshold_percent: u64) -> Option<Self> {
+        let median_secs = median(previous)?;
+        let regression_factor = 1.0 + regression_threshold_percent as f64 / 100.0;
+        Some(BootHistoryComparison {
+            secs,
+            median_secs,
+            delta_secs: secs - median_secs,
+            regressed: median_secs > 0.0 && secs > median_secs * regression_factor,
+        })
+    }
+}
+
+/// This boot compared to previous boots
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BootHistoryStats {
+    pub boot_id: String,
+    /// Number of previous boots compared against
+    pub previous_boots: u64,
+    /// Total boot time. None without previous boots.
+    pub total: Option<BootHistoryComparison>,
+    /// Units activated this boot and in at least one previous boot
+    pub units: HashMap<String, BootHistoryComparison>,
+    pub regressed_units: u64,
+}
+
+fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    match sorted.len() % 2 {
+        0 => Some((sorted[mid - 1] + sorted[mid]) / 2.0),
+        _ => Some(sorted[mid]),
+    }
+}
+
+/// Compare `current` against up to `history_size` of the most recent `previous` boots
+fn compare_boots(
+    current: &BootRecord,
+    previous: &[BootRecord],
+    history_size: usize,
+    regression_threshold_percent: u64,
+) -> BootHistoryStats {
+    let previous = &previous[previous.len().saturating_sub(history_size)..];
+    let mut stats = BootHistoryStats {
+        boot_id: current.boot_id.clone(),
+        previous_boots: previous.len() as u64,
+        ..Default::default()
+    };
+    let previous_totals: Vec<f64> = previous.iter().map(|b| b.total_secs).collect();
+    stats.total = BootHistoryComparison::new(
+        current.total_secs,
+        &previous_totals,
+        regression_threshold_percent,
+    );
+    for (unit, secs) in &current.unit_activation_secs {
+        let previous_secs: Vec<f64> = previous
+            .iter()
+            .filter_map(|b| b.unit_activation_secs.get(unit).copied())
+            .collect();
+        if let Some(comparison) =
+            BootHistoryComparison::new(*secs, &previous_secs, regression_threshold_percent)
+        {
+            stats.units.insert(unit.clone(), comparison);
+        }
+    }
+    stats.regressed_units = stats.units.values().filter(|u| u.regressed).count() as u64;
+    stats
+}
+
+fn history_file_path(history_dir: &Path) -> PathBuf {
+    history_dir.join(BOOT_HISTORY_FILE)
+}
+
+/// Read the history file. A file that doesn't parse is logged and replaced
+/// with a fresh history rather than failing every run.
+async fn read_boot_history(history_dir: &Path) -> Result<Vec<BootRecord>> {
+    let path = history_file_path(history_dir);
+    match tokio::fs::read(&path).await {
+        Ok(content) => match serde_json::from_slice(&content) {
+            Ok(history) => Ok(history),
+            Err(err) => {
+                warn!(
+                    "Unable to parse boot history {:?}, starting a fresh history: {}",
+                    path, err
+                );
+                Ok(Vec::new())
+            }
+        },
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Replace the history file atomically so a crash can't truncate it
+async fn write_boot_history(history_dir: &Path, history: &[BootRecord]) -> Result<()> {
+    tokio::fs::create_dir_all(history_dir).await?;
+    let path = history_file_path(history_dir);
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, serde_json::to_vec(history)?).await?;
+    tokio::fs::rename(&tmp_path, &path).await?;
+    Ok(())
+}
+
+/// Record this boot once startup has finished and compare it to previous boots.
+/// The `GetAll` calls made are added to `get_all_timings`.
+pub async fn update_boot_history_stats(
+    config: Arc<Config>,
+    connection: zbus::Connection,
+    machine_stats: Arc<RwLock<MachineStats>>,
+    get_all_timings: Arc<RwLock<GetAllTimings>>,
+) -> Result<()> {
+    let boot_id = get_boot_id()
+        .await
+        .map_err(|e| anyhow::anyhow!("Unable to get boot id for boot history: {}", e))?;
+    if machine_stats
+        .read()
+        .await
+        .boot_history
+        .as_ref()
+        .is_some_and(|history| history.boot_id == boot_id)
+    {
+        debug!("Using in-memory boot history stats");
+        return Ok(());
+    }
+
+    let history_dir = Path::new(&config.boot_history.history_dir);
+    let mut history = read_boot_history(history_dir).await?;
+    let current = match history.iter().position(|b| b.boot_id == boot_id) {
+        Some(idx) => history.remove(idx),
+        None => {
+            let boot_timeline = crate::boot_timeline::get_boot_timeline(&connection).await?;
+            if !boot_timeline.finished {
+                debug!("Startup has not finished. Not recording boot history yet");
+                return Ok(());
+            }
+            let mut timings = GetAllTimings::default();
+            let unit_activation_secs =
+                get_unit_activation_times(&config, &connection, &mut timings).await;
+            get_all_timings.write().await.add(&timings);
+            let unit_activation_secs = unit_activation_secs?.into_iter().collect();
+            BootRecord {
+                boot_id: boot_id.clone(),
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+                total_secs: boot_timeline.total_secs,
+                unit_activation_secs,
+            }
+        }
+    };
+
+    let history_size = config.boot_history.history_size as usize;
+    let stats = compare_boots(
+        &current,
+        &history,
+        history_size,
+        config.boot_history.regression_threshold_percent,
+    );
+    if stats.regressed_units > 0 {
+        info!(
+            "{} units regressed at boot compared to the previous {} boots",
+            stats.regressed_units, stats.previous_boots
+        );
+    }
+
+    // Keep this boot plus the previous history_size boots
+    history.push(current);
+    let excess = history.len().saturating_sub(history_size + 1);
+    history.drain(..excess);
+    write_boot_history(history_dir, &history).await?;
+
+    machine_stats.write().await.boot_history = Some(stats);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boot(boot_id: &str, total_secs: f64, foo_secs: f64) -> BootRecord {
+        BootRecord {
+            boot_id: boot_id.to_string(),
+            total_secs,
+            unit_activation_secs: HashMap::from([(String::from("foo.service"), foo_secs)]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(None, median(&[]));
+        assert_eq!(Some(2.0), median(&[3.0, 1.0, 2.0]));
+        assert_eq!(Some(2.5), median(&[4.0, 1.0, 2.0, 3.0]));
+    }
+
+    #[test]
+    fn test_compare_boots() {
+        let previous = vec![
+            // Dropped by history_size
+            boot("0", 100.0, 100.0),
+            boot("1", 10.0, 1.0),
+            boot("2", 12.0, 2.0),
+            boot("3", 11.0, 3.0),
+        ];
+        let mut current = boot("4", 12.0, 4.0);
+        current
+            .unit_activation_secs
+            .insert(String::from("new.service"), 1.0);
+
+        let stats = compare_boots(&current, &previous, 3, 50);
+        assert_eq!(3, stats.previous_boots);
+        assert_eq!(
+            Some(BootHistoryComparison {
+                secs: 12.0,
+                median_secs: 11.0,
+                delta_secs: 1.0,
+                regressed: false,
+            }),
+            stats.total
+        );
+        assert_eq!(
+            HashMap::from([(
+                String::from("foo.service"),
+                BootHistoryComparison {
+                    secs: 4.0,
+                    median_secs: 2.0,
+                    delta_secs: 2.0,
+                    regressed: true,
+                }
+            )]),
+            stats.units
+        );
+        assert_eq!(1, stats.regressed_units);
+
+        let first_boot = compare_boots(&current, &[], 3, 50);
+        assert_eq!(0, first_boot.previous_boots);
+        assert!(first_boot.total.is_none());
+        assert!(first_boot.units.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_boot_history_read_write_roundtrip() {
+        let temp_dir = tempfile::tempdir().expect("create temp dir");
+        assert!(read_boot_history(temp_dir.path())
+            .await
+            .expect("missing history should not error")
+            .is_empty());
+        let history = vec![boot("1", 10.0, 1.0), boot("2", 12.0, 2.0)];
+        write_boot_history(temp_dir.path(), &history)
+            .await
+            .expect("write history");
+        assert_eq!(
+            history,
+            read_boot_history(temp_dir.path())
+                .await
+                .expect("read history")
+        );
+
+        // A truncated file starts a fresh history
+        tokio::fs::write(history_file_path(temp_dir.path()), b"[{\"boot_id\":")
+            .await
+            .expect("truncate history");
+        assert!(read_boot_history(temp_dir.path())
+            .await
+            .expect("corrupt history should not error")
+            .is_empty());
+    }
+}
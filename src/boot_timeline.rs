@@ -0,0 +1,220 @@
+//! # boot_timeline module
+//!
+//! Collects how long each boot phase took from the systemd manager's boot
+//! timestamps. The same as `systemd-analyze time` plus the generator, unit
+//! load and security setup phases.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::dbus::zbus_systemd::ManagerProxy;
+use crate::MachineStats;
+
+/// Duration of each boot phase in seconds. Phases that did not happen on
+/// this boot (e.g. no initrd, or firmware times not exposed by the boot
+/// loader) are 0.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BootTimelineStats {
+    /// Whether startup has finished. Userspace and total are 0 until it has.
+    pub finished: bool,
+    pub firmware_secs: f64,
+    pub loader_secs: f64,
+    pub kernel_secs: f64,
+    pub initrd_secs: f64,
+    pub userspace_secs: f64,
+    /// firmware + loader + kernel + initrd + userspace
+    pub total_secs: f64,
+    pub generators_secs: f64,
+    pub units_load_secs: f64,
+    pub security_secs: f64,
+    pub initrd_generators_secs: f64,
+    pub initrd_units_load_secs: f64,
+    pub initrd_security_secs: f64,
+}
+
+/// Manager `*TimestampMonotonic` properties in microseconds. Firmware and
+/// loader are how long before the kernel started they began.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct BootTimestamps {
+    firmware: u64,
+    loader: u64,
+    initrd: u64,
+    userspace: u64,
+    finish: u64,
+    generators_start: u64,
+    generators_finish: u64,
+    units_load_start: u64,
+    units_load_finish: u64,
+    security_start: u64,
+    security_finish: u64,
+    initrd_generators_start: u64,
+    initrd_generators_finish: u64,
+    initrd_units_load_start: u64,
+    initrd_units_load_finish: u64,
+    initrd_security_start: u64,
+    initrd_security_finish: u64,
+}
+
+fn usec_to_secs(usec: u64) -> f64 {
+    usec as f64 / 1_000_000.0
+}
+
+/// Length of a phase, 0 if it did not start or finish
+fn phase_secs(start: u64, finish: u64) -> f64 {
+    if start == 0 || finish == 0 {
+        return 0.0;
+    }
+    usec_to_secs(finish.saturating_sub(start))
+}
+
+/// Same calculations as systemd-analyze's `acquire_boot_times()` / `pretty_boot_time()`
+fn compute_boot_timeline(ts: &BootTimestamps) -> BootTimelineStats {
+    let mut stats = BootTimelineStats {
+        finished: ts.finish > 0,
+        generators_secs: phase_secs(ts.generators_start, ts.generators_finish),
+        units_load_secs: phase_secs(ts.units_load_start, ts.units_load_finish),
+        security_secs: phase_secs(ts.security_start, ts.security_finish),
+        initrd_generators_secs: phase_secs(ts.initrd_generators_start, ts.initrd_generators_finish),
+        initrd_units_load_secs: phase_secs(ts.initrd_units_load_start, ts.initrd_units_load_finish),
+        initrd_security_secs: phase_secs(ts.initrd_security_start, ts.initrd_security_finish),
+        ..Default::default()
+    };
+    if ts.firmware > 0 {
+        stats.firmware_secs = usec_to_secs(ts.firmware.saturating_sub(ts.loader));
+    }
+    stats.loader_secs = usec_to_secs(ts.loader);
+    // The kernel starts at monotonic 0 and ends when the initrd or real root starts
+    if ts.initrd > 0 {
+        stats.kernel_secs = usec_to_secs(ts.initrd);
+        stats.initrd_secs = usec_to_secs(ts.userspace.saturating_sub(ts.initrd));
+    } else {
+        stats.kernel_secs = usec_to_secs(ts.userspace);
+    }
+    if stats.finished {
+        stats.userspace_secs = usec_to_secs(ts.finish.saturating_sub(ts.userspace));
+        stats.total_secs = usec_to_secs(ts.firmware + ts.finish);
+    }
+    stats
+}
+
+async fn get_boot_timestamps(manager: &ManagerProxy<'_>) -> zbus::Result<BootTimestamps> {
+    Ok(BootTimestamps {
+        firmware: manager.firmware_timestamp_monotonic().await?,
+        loader: manager.loader_timestamp_monotonic().await?,
+        initrd: manager.init_rdtimestamp_monotonic().await?,
+        userspace: manager.userspace_timestamp_monotonic().await?,
+        finish: manager.finish_timestamp_monotonic().await?,
+        generators_start: manager.generators_start_timestamp_monotonic().await?,
+        generators_finish: manager.generators_finish_timestamp_monotonic().await?,
+        units_load_start: manager.units_load_start_timestamp_monotonic().await?,
+        units_load_finish: manager.units_load_finish_timestamp_monotonic().await?,
+        security_start: manager.security_start_timestamp_monotonic().await?,
+        security_finish: manager.security_finish_timestamp_monotonic().await?,
+        initrd_generators_start: manager
+            .init_rdgenerators_start_timestamp_monotonic()
+            .await?,
+        initrd_generators_finish: manager
+            .init_rdgenerators_finish_timestamp_monotonic()
+            .await?,
+        initrd_units_load_start: manager
+            .init_rdunits_load_start_timestamp_monotonic()
+            .await?,
+        initrd_units_load_finish: manager
+            .init_rdunits_load_finish_timestamp_monotonic()
+            .await?,
+        initrd_security_start: manager.init_rdsecurity_start_timestamp_monotonic().await?,
+        initrd_security_finish: manager.init_rdsecurity_finish_timestamp_monotonic().await?,
+    })
+}
+
+/// Fetch the manager's boot timestamps and work out the boot phases
+pub(crate) async fn get_boot_timeline(connection: &zbus::Connection) -> Result<BootTimelineStats> {
+    let systemd_proxy = ManagerProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let boot_timestamps = get_boot_timestamps(&systemd_proxy).await?;
+    Ok(compute_boot_timeline(&boot_timestamps))
+}
+
+/// Update boot timeline stats. Boot times can't change once startup has
+/// finished so later runs reuse the stats in memory.
+pub async fn update_boot_timeline_stats(
+    connection: zbus::Connection,
+    machine_stats: Arc<RwLock<MachineStats>>,
+) -> Result<()> {
+    if machine_stats
+        .read()
+        .await
+        .boot_timeline
+        .as_ref()
+        .is_some_and(|timeline| timeline.finished)
+    {
+        debug!("Using in-memory boot timeline stats");
+        return Ok(());
+    }
+
+    let boot_timeline = get_boot_timeline(&connection).await?;
+    debug!("Collected boot timeline: {:?}", boot_timeline);
+
+    machine_stats.write().await.boot_timeline = Some(boot_timeline);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_boot_timeline_with_initrd() {
+        let boot_timestamps = BootTimestamps {
+            firmware: 5_000_000,
+            loader: 2_000_000,
+            initrd: 1_500_000,
+            userspace: 4_000_000,
+            finish: 10_000_000,
+            generators_start: 4_100_000,
+            generators_finish: 4_350_000,
+            units_load_start: 4_400_000,
+            units_load_finish: 4_500_000,
+            initrd_generators_start: 1_600_000,
+            initrd_generators_finish: 1_700_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            BootTimelineStats {
+                finished: true,
+                firmware_secs: 3.0,
+                loader_secs: 2.0,
+                kernel_secs: 1.5,
+                initrd_secs: 2.5,
+                userspace_secs: 6.0,
+                total_secs: 15.0,
+                generators_secs: 0.25,
+                units_load_secs: 0.1,
+                initrd_generators_secs: 0.1,
+                ..Default::default()
+            },
+            compute_boot_timeline(&boot_timestamps)
+        );
+    }
+
+    #[test]
+    fn test_compute_boot_timeline_unfinished() {
+        let boot_timestamps = BootTimestamps {
+            userspace: 2_000_000,
+            units_load_start: 2_100_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            BootTimelineStats {
+                kernel_secs: 2.0,
+                ..Default::default()
+            },
+            compute_boot_timeline(&boot_timestamps)
+        );
+    }
+}
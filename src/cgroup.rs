@@ -0,0 +1,420 @@
+//! # cgroup module
+//!
+//! Helpers to read cgroup v2 interface files of systemd units. A unit's
+//! `ControlGroup` property (e.g. `/system.slice/foo.service`) is relative to
+//! the cgroup2 mount.
+//! Ref: <https://docs.kernel.org/admin-guide/cgroup-v2.html#interface-files>
+//! PSI ref: <https://docs.kernel.org/accounting/psi.html>
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tracing::debug;
+use zbus::zvariant::ObjectPath;
+
+/// Where systemd mounts the unified cgroup hierarchy
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// Path of a unit's cgroup directory under `cgroup_root`
+pub fn cgroup_path(cgroup_root: &Path, control_group: &str) -> PathBuf {
+    cgroup_root.join(control_group.trim_start_matches('/'))
+}
+
+/// Parse a flat keyed file, one `key value` pair per line (e.g.
+/// `memory.events`, `/proc/vmstat`). Malformed lines are skipped.
+pub fn parse_flat_keyed(content: &str) -> HashMap<String, u64> {
+    let mut values = HashMap::new();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(key), Some(value), None) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        match value.parse::<u64>() {
+            Ok(value) => {
+                values.insert(key.to_string(), value);
+            }
+            Err(err) => debug!("Skipping flat keyed line {:?}: {}", line, err),
+        }
+    }
+    values
+}
+
+pub async fn read_flat_keyed(path: &Path) -> std::io::Result<HashMap<String, u64>> {
+    Ok(parse_flat_keyed(&tokio::fs::read_to_string(path).await?))
+}
+
+/// A unit's `ControlGroup`. Empty when the unit has no cgroup (not running).
+pub(crate) async fn get_control_group(
+    connection: &zbus::Connection,
+    unit_object_path: ObjectPath<'_>,
+) -> zbus::Result<String> {
+    let sp = crate::dbus::zbus_service::ServiceProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .path(unit_object_path)?
+        .build()
+        .await?;
+    sp.control_group().await
+}
+
+/// Resource usage read from a cgroup's interface files, named like the
+/// matching `ServiceStats` fields. None when the file is missing (controller
+/// not enabled for the cgroup, older kernel or the root cgroup).
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CgroupStats {
+    /// `usage_usec` of `cpu.stat` in nanoseconds
+    pub cpuusage_nsec: Option<u64>,
+    /// `rbytes` of `io.stat` summed over all devices
+    pub ioread_bytes: Option<u64>,
+    /// `rios` of `io.stat` summed over all devices
+    pub ioread_operations: Option<u64>,
+    /// `wbytes` of `io.stat` summed over all devices
+    pub iowrite_bytes: Option<u64>,
+    /// `wios` of `io.stat` summed over all devices
+    pub iowrite_operations: Option<u64>,
+    /// `memory.current` in bytes
+    pub memory_current: Option<u64>,
+    /// `memory.peak` in bytes (kernel 5.19+)
+    pub memory_peak: Option<u64>,
+    /// `pids.current`
+    pub tasks_current: Option<u64>,
+}
+
+/// Parse a nested keyed file, `<device> key=value ...` per line (e.g.
+/// `io.stat`), summing each key over every device
+pub fn parse_nested_keyed_sum(content: &str) -> HashMap<String, u64> {
+    let mut values = HashMap::new();
+    for line in content.lines() {
+        for field in line.split_whitespace().skip(1) {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+            match value.parse::<u64>() {
+                Ok(value) => *values.entry(key.to_string()).or_default() += value,
+                Err(err) => debug!("Skipping nested keyed field {:?}: {}", field, err),
+            }
+        }
+    }
+    values
+}
+
+/// Read a single value file such as `memory.current`
+async fn read_single_value(path: PathBuf) -> Option<u64> {
+    let content = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(err) => {
+            debug!("Unable to read {}: {}", path.display(), err);
+            return None;
+        }
+    };
+    match content.trim().parse() {
+        Ok(value) => Some(value),
+        Err(err) => {
+            debug!("Unable to parse {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+async fn read_keyed(path: PathBuf, nested: bool) -> Option<HashMap<String, u64>> {
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) if nested => Some(parse_nested_keyed_sum(&content)),
+        Ok(content) => Some(parse_flat_keyed(&content)),
+        Err(err) => {
+            debug!("Unable to read {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// Read the resource usage of the cgroup directory `cgroup_dir`
+pub async fn read_cgroup_stats(cgroup_dir: &Path) -> CgroupStats {
+    let (cpu_stat, io_stat, memory_current, memory_peak, tasks_current) = tokio::join!(
+        read_keyed(cgroup_dir.join("cpu.stat"), false),
+        read_keyed(cgroup_dir.join("io.stat"), true),
+        read_single_value(cgroup_dir.join("memory.current")),
+        read_single_value(cgroup_dir.join("memory.peak")),
+        read_single_value(cgroup_dir.join("pids.current")),
+    );
+    // A device only shows up in io.stat once it has been used
+    let io_value = |key: &str| {
+        io_stat
+            .as_ref()
+            .map(|io_stat| io_stat.get(key).copied().unwrap_or_default())
+    };
+    CgroupStats {
+        cpuusage_nsec: cpu_stat
+            .as_ref()
+            .and_then(|cpu_stat| cpu_stat.get("usage_usec"))
+            .map(|usage_usec| usage_usec.saturating_mul(1000)),
+        ioread_bytes: io_value("rbytes"),
+        ioread_operations: io_value("rios"),
+        iowrite_bytes: io_value("wbytes"),
+        iowrite_operations: io_value("wios"),
+        memory_current,
+        memory_peak,
+        tasks_current,
+    }
+}
+
+/// One line of a PSI file. The averages are the percentage of wall time
+/// tasks were stalled over the last 10, 60 and 300 seconds.
+///
+/// `Eq` holds as `parse_pressure` never produces a NaN average, which keeps
+/// `ServiceStats` `Eq` with its `pressure` field.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PressureLine {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    /// Total stall time in microseconds
+    pub total_usec: u64,
+}
+impl Eq for PressureLine {}
+
+/// `some`: at least one task was stalled. `full`: every non-idle task was
+/// stalled at once (zero for cpu on kernels before 5.13).
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ResourcePressure {
+    pub some: PressureLine,
+    pub full: PressureLine,
+}
+
+/// `cpu.pressure`, `memory.pressure` and `io.pressure` of a cgroup. None
+/// when the file can't be read (e.g. PSI disabled with psi=0).
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CgroupPressure {
+    pub cpu: Option<ResourcePressure>,
+    pub memory: Option<ResourcePressure>,
+    pub io: Option<ResourcePressure>,
+}
+
+impl CgroupPressure {
+    /// (resource, kind, line) for every resource that was read
+    pub fn lines(&self) -> Vec<(&'static str, &'static str, &PressureLine)> {
+        let mut lines = Vec::new();
+        for (resource, pressure) in [
+            ("cpu", &self.cpu),
+            ("memory", &self.memory),
+            ("io", &self.io),
+        ] {
+            if let Some(pressure) = pressure {
+                lines.push((resource, "some", &pressure.some));
+                lines.push((resource, "full", &pressure.full));
+            }
+        }
+        lines
+    }
+}
+
+/// A PSI average, 0 unless it is a finite number
+fn parse_average(value: &str) -> f64 {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|average| average.is_finite())
+        .unwrap_or_default()
+}
+
+/// Parse a PSI file:
+/// `some avg10=0.00 avg60=0.00 avg300=0.00 total=0`
+/// `full avg10=0.00 avg60=0.00 avg300=0.00 total=0`
+pub fn parse_pressure(content: &str) -> ResourcePressure {
+    let mut pressure = ResourcePressure::default();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let pressure_line = match fields.next() {
+            Some("some") => &mut pressure.some,
+            Some("full") => &mut pressure.full,
+            _ => continue,
+        };
+        for field in fields {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+            match key {
+                "avg10" => pressure_line.avg10 = parse_average(value),
+                "avg60" => pressure_line.avg60 = parse_average(value),
+                "avg300" => pressure_line.avg300 = parse_average(value),
+                "total" => pressure_line.total_usec = value.parse().unwrap_or_default(),
+                _ => (),
+            }
+        }
+    }
+    pressure
+}
+
+async fn read_pressure(path: PathBuf) -> Option<ResourcePressure> {
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) => Some(parse_pressure(&content)),
+        Err(err) => {
+            debug!("Unable to read {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// Read the PSI files of the cgroup directory `cgroup_dir`
+pub async fn read_cgroup_pressure(cgroup_dir: &Path) -> CgroupPressure {
+    let (cpu, memory, io) = tokio::join!(
+        read_pressure(cgroup_dir.join("cpu.pressure")),
+        read_pressure(cgroup_dir.join("memory.pressure")),
+        read_pressure(cgroup_dir.join("io.pressure")),
+    );
+    CgroupPressure { cpu, memory, io }
+}
+
+/// Cgroup path of a slice unit. Dashes in slice names are the hierarchy:
+/// `user-1000.slice` is `user.slice/user-1000.slice` and `-.slice` is the root.
+pub fn slice_cgroup_path(cgroup_root: &Path, slice: &str) -> PathBuf {
+    let mut path = cgroup_root.to_path_buf();
+    let Some(name) = slice.strip_suffix(".slice") else {
+        return path.join(slice);
+    };
+    if name == "-" {
+        return path;
+    }
+    let mut prefix = String::new();
+    for part in name.split('-') {
+        if !prefix.is_empty() {
+            prefix.push('-');
+        }
+        prefix.push_str(part);
+        path.push(format!("{prefix}.slice"));
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cgroup_path() {
+        assert_eq!(
+            PathBuf::from("/sys/fs/cgroup/system.slice/foo.service"),
+            cgroup_path(Path::new(CGROUP_ROOT), "/system.slice/foo.service")
+        );
+    }
+
+    #[test]
+    fn test_parse_flat_keyed() {
+        let content = "low 0\nhigh 12\nmax 3\noom 1\noom_kill 1\noom_group_kill 0\nbogus\nnan x\n";
+        let values = parse_flat_keyed(content);
+        assert_eq!(6, values.len());
+        assert_eq!(Some(&12), values.get("high"));
+        assert_eq!(Some(&1), values.get("oom_kill"));
+        assert_eq!(None, values.get("nan"));
+    }
+
+    #[test]
+    fn test_parse_nested_keyed_sum() {
+        let content = "8:0 rbytes=1024 wbytes=2048 rios=1 wios=2 dbytes=0 dios=0\n\
+                       259:0 rbytes=100 wbytes=0 rios=3 wios=0 dbytes=0 dios=0\n";
+        let values = parse_nested_keyed_sum(content);
+        assert_eq!(Some(&1124), values.get("rbytes"));
+        assert_eq!(Some(&2048), values.get("wbytes"));
+        assert_eq!(Some(&4), values.get("rios"));
+        assert!(parse_nested_keyed_sum("").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_cgroup_stats() {
+        let temp_dir = tempfile::tempdir().expect("create temp dir");
+        for (file, content) in [
+            (
+                "cpu.stat",
+                "usage_usec 1500\nuser_usec 1000\nsystem_usec 500\n",
+            ),
+            ("io.stat", ""),
+            ("memory.current", "4096\n"),
+            ("pids.current", "3\n"),
+        ] {
+            tokio::fs::write(temp_dir.path().join(file), content)
+                .await
+                .expect("write cgroup file");
+        }
+        assert_eq!(
+            CgroupStats {
+                cpuusage_nsec: Some(1_500_000),
+                ioread_bytes: Some(0),
+                ioread_operations: Some(0),
+                iowrite_bytes: Some(0),
+                iowrite_operations: Some(0),
+                memory_current: Some(4096),
+                memory_peak: None,
+                tasks_current: Some(3),
+            },
+            read_cgroup_stats(temp_dir.path()).await
+        );
+    }
+
+    #[test]
+    fn test_parse_pressure() {
+        let content = "some avg10=1.50 avg60=0.25 avg300=0.00 total=123456\n\
+                       full avg10=0.50 avg60=0.00 avg300=0.00 total=789\n";
+        assert_eq!(
+            ResourcePressure {
+                some: PressureLine {
+                    avg10: 1.5,
+                    avg60: 0.25,
+                    avg300: 0.0,
+                    total_usec: 123456,
+                },
+                full: PressureLine {
+                    avg10: 0.5,
+                    total_usec: 789,
+                    ..Default::default()
+                },
+            },
+            parse_pressure(content)
+        );
+        // cpu.pressure before kernel 5.13 has no full line
+        assert_eq!(
+            PressureLine::default(),
+            parse_pressure("some avg10=0.00 avg60=0.00 avg300=0.00 total=5\n").full
+        );
+        // A NaN average would break Eq
+        assert_eq!(
+            0.0,
+            parse_pressure("some avg10=nan avg60=inf avg300=0.00 total=5\n")
+                .some
+                .avg10
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_cgroup_pressure() {
+        let temp_dir = tempfile::tempdir().expect("create temp dir");
+        tokio::fs::write(
+            temp_dir.path().join("memory.pressure"),
+            "some avg10=2.00 avg60=1.00 avg300=0.50 total=42\n",
+        )
+        .await
+        .expect("write memory.pressure");
+        let pressure = read_cgroup_pressure(temp_dir.path()).await;
+        assert!(pressure.cpu.is_none());
+        assert!(pressure.io.is_none());
+        assert_eq!(
+            42,
+            pressure.memory.expect("memory pressure").some.total_usec
+        );
+    }
+
+    #[test]
+    fn test_slice_cgroup_path() {
+        let root = Path::new(CGROUP_ROOT);
+        assert_eq!(
+            PathBuf::from("/sys/fs/cgroup/system.slice"),
+            slice_cgroup_path(root, "system.slice")
+        );
+        assert_eq!(
+            PathBuf::from("/sys/fs/cgroup/user.slice/user-1000.slice"),
+            slice_cgroup_path(root, "user-1000.slice")
+        );
+        assert_eq!(
+            PathBuf::from(CGROUP_ROOT),
+            slice_cgroup_path(root, "-.slice")
+        );
+    }
+}
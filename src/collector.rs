@@ -0,0 +1,123 @@
+//! # collector module
+//!
+//! Custom collectors for library users. Each registered [`Collector`] runs in
+//! parallel with the built-in collectors every run, is timed into
+//! `collector_timings` and its returned JSON value is stored under its name in
+//! `MonitordStats::extra`, which every output format emits. A failed run
+//! removes the collector's value.
+
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+
+use crate::config::Config;
+
+/// A source of stats that is not built into monitord
+pub trait Collector: Send + Sync {
+    /// Unique name used as the `extra` key and in `collector_timings`
+    fn name(&self) -> &str;
+
+    /// Whether to run this collector with the current config
+    fn enabled(&self, _config: &Config) -> bool {
+        true
+    }
+
+    /// Collect stats, usually via the shared system bus connection
+    fn collect<'a>(
+        &'a self,
+        connection: &'a zbus::Connection,
+        config: &'a Config,
+    ) -> BoxFuture<'a, anyhow::Result<serde_json::Value>>;
+}
+
+/// Collectors to run alongside the built-in ones, in registration order
+#[derive(Clone, Default)]
+pub struct CollectorRegistry {
+    collectors: Vec<Arc<dyn Collector>>,
+}
+
+impl CollectorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a collector. Registering a name twice replaces the earlier collector.
+    pub fn register(&mut self, collector: impl Collector + 'static) -> &mut Self {
+        let name = collector.name().to_string();
+        self.collectors.retain(|c| c.name() != name);
+        self.collectors.push(Arc::new(collector));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.collectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.collectors.is_empty()
+    }
+
+    /// Collectors enabled by `config`
+    pub fn enabled<'a>(
+        &'a self,
+        config: &'a Config,
+    ) -> impl Iterator<Item = &'a Arc<dyn Collector>> {
+        self.collectors.iter().filter(move |c| c.enabled(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestCollector {
+        name: &'static str,
+        enabled: bool,
+    }
+
+    impl Collector for TestCollector {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn enabled(&self, _config: &Config) -> bool {
+            self.enabled
+        }
+
+        fn collect<'a>(
+            &'a self,
+            _connection: &'a zbus::Connection,
+            _config: &'a Config,
+        ) -> BoxFuture<'a, anyhow::Result<serde_json::Value>> {
+            Box::pin(async { Ok(serde_json::Value::from(1)) })
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = CollectorRegistry::new();
+        assert!(registry.is_empty());
+        registry
+            .register(TestCollector {
+                name: "foo",
+                enabled: false,
+            })
+            .register(TestCollector {
+                name: "bar",
+                enabled: true,
+            })
+            .register(TestCollector {
+                name: "foo",
+                enabled: true,
+            });
+        assert_eq!(2, registry.len());
+        let config = Config::default();
+        assert_eq!(
+            vec!["bar", "foo"],
+            registry
+                .enabled(&config)
+                .map(|c| c.name())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -39,6 +40,12 @@ pub enum MonitordOutputFormat {
         serialize = "jsonpretty"
     )]
     JsonPretty = 2,
+    #[strum(
+        serialize = "influx-line",
+        serialize = "influx_line",
+        serialize = "influxline"
+    )]
+    InfluxLine = 3,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -49,6 +56,8 @@ pub struct MonitordConfig {
     pub key_prefix: String,
     pub output_format: MonitordOutputFormat,
     pub dbus_timeout: u64,
+    /// Address to serve Prometheus metrics on (`/metrics`) in daemon mode
+    pub prometheus_listen_address: Option<SocketAddr>,
 }
 impl Default for MonitordConfig {
     fn default() -> Self {
@@ -59,6 +68,7 @@ impl Default for MonitordConfig {
             key_prefix: "".to_string(),
             output_format: MonitordOutputFormat::default(),
             dbus_timeout: 30,
+            prometheus_listen_address: None,
         }
     }
 }
@@ -87,6 +97,46 @@ impl Default for Pid1Config {
     }
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ManagerConfig {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ManagerDefaultsConfig {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TaintedConfig {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct WatchdogConfig {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OomConfig {
+    pub enabled: bool,
+    /// Also track every service passing the [units] state_stats allowlist
+    /// and blocklist, not just [services]
+    pub all_services: bool,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PressureConfig {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SlicesConfig {
+    pub enabled: bool,
+    pub allowlist: HashSet<String>,
+    pub blocklist: HashSet<String>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SystemStateConfig {
     pub enabled: bool,
@@ -130,6 +180,14 @@ pub struct UnitsConfig {
     /// Number of slowest units (by per-unit collection duration) to record in
     /// `UnitsCollectionTimings::slowest_units`. Set to 0 to disable.
     pub slowest_units_count: u64,
+    /// Daemon mode only: follow systemd unit/job signals between runs to count
+    /// per-unit state transitions
+    pub event_tracking: bool,
+    /// Read service CPU, memory, IO and task counters from cgroupfs instead
+    /// of D-Bus, falling back to D-Bus when the cgroup isn't readable. The
+    /// `GetAll` call fetches the D-Bus values either way, so this only changes
+    /// where the values come from.
+    pub cgroup_fast_path: bool,
 }
 impl Default for UnitsConfig {
     fn default() -> Self {
@@ -143,6 +201,8 @@ impl Default for UnitsConfig {
             unit_files: true,
             per_unit_concurrency: 8,
             slowest_units_count: 5,
+            event_tracking: false,
+            cgroup_fast_path: false,
         }
     }
 }
@@ -231,6 +291,47 @@ impl Default for BootBlameConfig {
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BootCriticalChainConfig {
+    pub enabled: bool,
+    /// Unit to walk the critical chain back from
+    pub target: String,
+}
+impl Default for BootCriticalChainConfig {
+    fn default() -> Self {
+        BootCriticalChainConfig {
+            enabled: false,
+            target: "default.target".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BootHistoryConfig {
+    pub enabled: bool,
+    /// Number of previous boots to keep and compare against
+    pub history_size: u64,
+    /// Flag units slower than their median by more than this percentage
+    pub regression_threshold_percent: u64,
+    /// Directory holding boot_history.json. Must survive reboots.
+    pub history_dir: String,
+}
+impl Default for BootHistoryConfig {
+    fn default() -> Self {
+        BootHistoryConfig {
+            enabled: false,
+            history_size: 10,
+            regression_threshold_percent: 50,
+            history_dir: "/var/lib/monitord".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BootTimelineConfig {
+    pub enabled: bool,
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct VerifyConfig {
     pub enabled: bool,
@@ -238,11 +339,111 @@ pub struct VerifyConfig {
     pub blocklist: HashSet<String>,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobsConfig {
+    pub enabled: bool,
+    /// Report queued jobs older than this as stuck
+    pub stuck_job_threshold_secs: u64,
+}
+impl Default for JobsConfig {
+    fn default() -> Self {
+        JobsConfig {
+            enabled: false,
+            stuck_job_threshold_secs: 300,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StdoutConfig {
+    /// Print stats in `[monitord] output_format` after every run
+    pub enabled: bool,
+}
+impl Default for StdoutConfig {
+    fn default() -> Self {
+        StdoutConfig { enabled: true }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatsdConfig {
+    pub enabled: bool,
+    /// host:port of the StatsD agent
+    pub address: String,
+    /// Send unit, machine, interface etc. names as DogStatsD tags rather than in the key
+    pub dogstatsd_tags: bool,
+    /// Max UDP payload size. Lines are batched into packets up to this size.
+    pub max_packet_bytes: u64,
+}
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        StatsdConfig {
+            enabled: false,
+            address: "127.0.0.1:8125".to_string(),
+            dogstatsd_tags: false,
+            max_packet_bytes: 1432,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GraphiteConfig {
+    pub enabled: bool,
+    /// host:port of the carbon relay plaintext listener
+    pub address: String,
+    /// Max lines kept while the relay is unreachable. Oldest are dropped first.
+    pub max_buffered_lines: u64,
+}
+impl Default for GraphiteConfig {
+    fn default() -> Self {
+        GraphiteConfig {
+            enabled: false,
+            address: "127.0.0.1:2003".to_string(),
+            max_buffered_lines: 100000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextfileConfig {
+    pub enabled: bool,
+    /// `.prom` file in node_exporter's textfile collector directory
+    pub path: PathBuf,
+}
+impl Default for TextfileConfig {
+    fn default() -> Self {
+        TextfileConfig {
+            enabled: false,
+            path: "/var/lib/node_exporter/textfile_collector/monitord.prom".into(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct VarlinkConfig {
     pub enabled: bool,
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DbusServiceConfig {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VarlinkServerConfig {
+    pub enabled: bool,
+    /// Unix socket to serve io.monitord.Stats on
+    pub socket_path: PathBuf,
+}
+impl Default for VarlinkServerConfig {
+    fn default() -> Self {
+        VarlinkServerConfig {
+            enabled: false,
+            socket_path: "/run/monitord/io.monitord.Stats".into(),
+        }
+    }
+}
+
 /// Config struct
 /// Each section represents an ini file section
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -253,12 +454,29 @@ pub struct Config {
     pub pid1: Pid1Config,
     pub services: HashSet<String>,
     pub system_state: SystemStateConfig,
+    pub manager: ManagerConfig,
+    pub manager_defaults: ManagerDefaultsConfig,
+    pub tainted: TaintedConfig,
+    pub watchdog: WatchdogConfig,
+    pub oom: OomConfig,
+    pub pressure: PressureConfig,
+    pub slices: SlicesConfig,
     pub timers: TimersConfig,
     pub units: UnitsConfig,
     pub dbus_stats: DBusStatsConfig,
     pub boot_blame: BootBlameConfig,
+    pub boot_timeline: BootTimelineConfig,
+    pub boot_critical_chain: BootCriticalChainConfig,
+    pub boot_history: BootHistoryConfig,
     pub verify: VerifyConfig,
+    pub jobs: JobsConfig,
     pub varlink: VarlinkConfig,
+    pub varlink_server: VarlinkServerConfig,
+    pub dbus_service: DbusServiceConfig,
+    pub stdout: StdoutConfig,
+    pub statsd: StatsdConfig,
+    pub graphite: GraphiteConfig,
+    pub textfile: TextfileConfig,
 }
 
 impl TryFrom<Ini> for Config {
@@ -295,6 +513,18 @@ impl TryFrom<Ini> for Config {
                 key: "output_format".into(),
                 reason: e.to_string(),
             })?;
+        if let Some(prometheus_listen_address) =
+            ini_config.get("monitord", "prometheus_listen_address")
+        {
+            config.monitord.prometheus_listen_address =
+                Some(prometheus_listen_address.parse().map_err(
+                    |e: std::net::AddrParseError| MonitordConfigError::InvalidValue {
+                        section: "monitord".into(),
+                        key: "prometheus_listen_address".into(),
+                        reason: e.to_string(),
+                    },
+                )?);
+        }
 
         // [networkd] section
         config.networkd.enabled = read_config_bool(&ini_config, "networkd", "enabled")?;
@@ -314,6 +544,58 @@ impl TryFrom<Ini> for Config {
         // [system-state] section
         config.system_state.enabled = read_config_bool(&ini_config, "system-state", "enabled")?;
 
+        // [manager] section
+        if let Some(manager_enabled) = read_config_optional_bool(&ini_config, "manager", "enabled")?
+        {
+            config.manager.enabled = manager_enabled;
+        }
+
+        // [manager-defaults] section
+        if let Some(manager_defaults_enabled) =
+            read_config_optional_bool(&ini_config, "manager-defaults", "enabled")?
+        {
+            config.manager_defaults.enabled = manager_defaults_enabled;
+        }
+
+        // [tainted] section
+        if let Some(tainted_enabled) = read_config_optional_bool(&ini_config, "tainted", "enabled")?
+        {
+            config.tainted.enabled = tainted_enabled;
+        }
+
+        // [watchdog] section
+        if let Some(watchdog_enabled) =
+            read_config_optional_bool(&ini_config, "watchdog", "enabled")?
+        {
+            config.watchdog.enabled = watchdog_enabled;
+        }
+
+        // [oom] section
+        if let Some(oom_enabled) = read_config_optional_bool(&ini_config, "oom", "enabled")? {
+            config.oom.enabled = oom_enabled;
+        }
+        if let Some(all_services) = read_config_optional_bool(&ini_config, "oom", "all_services")? {
+            config.oom.all_services = all_services;
+        }
+
+        // [pressure] section
+        if let Some(pressure_enabled) =
+            read_config_optional_bool(&ini_config, "pressure", "enabled")?
+        {
+            config.pressure.enabled = pressure_enabled;
+        }
+
+        // [slices] section
+        if let Some(slices_enabled) = read_config_optional_bool(&ini_config, "slices", "enabled")? {
+            config.slices.enabled = slices_enabled;
+        }
+        if let Some(slices_allowlist) = config_map.get("slices.allowlist") {
+            config.slices.allowlist = slices_allowlist.keys().map(|s| s.to_string()).collect();
+        }
+        if let Some(slices_blocklist) = config_map.get("slices.blocklist") {
+            config.slices.blocklist = slices_blocklist.keys().map(|s| s.to_string()).collect();
+        }
+
         // [timers] section
         config.timers.enabled = read_config_bool(&ini_config, "timers", "enabled")?;
         if let Some(timers_allowlist) = config_map.get("timers.allowlist") {
@@ -355,6 +637,16 @@ impl TryFrom<Ini> for Config {
         if let Ok(Some(slowest_units_count)) = ini_config.getuint("units", "slowest_units_count") {
             config.units.slowest_units_count = slowest_units_count;
         }
+        if let Some(event_tracking) =
+            read_config_optional_bool(&ini_config, "units", "event_tracking")?
+        {
+            config.units.event_tracking = event_tracking;
+        }
+        if let Some(cgroup_fast_path) =
+            read_config_optional_bool(&ini_config, "units", "cgroup_fast_path")?
+        {
+            config.units.cgroup_fast_path = cgroup_fast_path;
+        }
 
         // [machines] section
         config.machines.enabled = read_config_bool(&ini_config, "machines", "enabled")?;
@@ -429,6 +721,41 @@ impl TryFrom<Ini> for Config {
             config.boot_blame.blocklist = boot_blocklist.keys().map(|s| s.to_string()).collect();
         }
 
+        // [boot-timeline] section
+        if let Some(boot_timeline_enabled) =
+            read_config_optional_bool(&ini_config, "boot-timeline", "enabled")?
+        {
+            config.boot_timeline.enabled = boot_timeline_enabled;
+        }
+
+        // [boot-critical-chain] section
+        if let Some(boot_critical_chain_enabled) =
+            read_config_optional_bool(&ini_config, "boot-critical-chain", "enabled")?
+        {
+            config.boot_critical_chain.enabled = boot_critical_chain_enabled;
+        }
+        if let Some(target) = ini_config.get("boot-critical-chain", "target") {
+            config.boot_critical_chain.target = target;
+        }
+
+        // [boot-history] section
+        if let Some(boot_history_enabled) =
+            read_config_optional_bool(&ini_config, "boot-history", "enabled")?
+        {
+            config.boot_history.enabled = boot_history_enabled;
+        }
+        if let Ok(Some(history_size)) = ini_config.getuint("boot-history", "history_size") {
+            config.boot_history.history_size = history_size;
+        }
+        if let Ok(Some(regression_threshold_percent)) =
+            ini_config.getuint("boot-history", "regression_threshold_percent")
+        {
+            config.boot_history.regression_threshold_percent = regression_threshold_percent;
+        }
+        if let Some(history_dir) = ini_config.get("boot-history", "history_dir") {
+            config.boot_history.history_dir = history_dir;
+        }
+
         // [verify] section
         config.verify.enabled = read_config_bool(&ini_config, "verify", "enabled")?;
         if let Some(verify_allowlist) = config_map.get("verify.allowlist") {
@@ -438,9 +765,80 @@ impl TryFrom<Ini> for Config {
             config.verify.blocklist = verify_blocklist.keys().map(|s| s.to_string()).collect();
         }
 
+        // [jobs] section
+        if let Some(jobs_enabled) = read_config_optional_bool(&ini_config, "jobs", "enabled")? {
+            config.jobs.enabled = jobs_enabled;
+        }
+        if let Ok(Some(stuck_job_threshold_secs)) =
+            ini_config.getuint("jobs", "stuck_job_threshold_secs")
+        {
+            config.jobs.stuck_job_threshold_secs = stuck_job_threshold_secs;
+        }
+
         // [varlink] section
         config.varlink.enabled = read_config_bool(&ini_config, "varlink", "enabled")?;
 
+        // [varlink-server] section
+        if let Some(varlink_server_enabled) =
+            read_config_optional_bool(&ini_config, "varlink-server", "enabled")?
+        {
+            config.varlink_server.enabled = varlink_server_enabled;
+        }
+        if let Some(socket_path) = ini_config.get("varlink-server", "socket_path") {
+            config.varlink_server.socket_path = socket_path.into();
+        }
+
+        // [dbus-service] section
+        if let Some(dbus_service_enabled) =
+            read_config_optional_bool(&ini_config, "dbus-service", "enabled")?
+        {
+            config.dbus_service.enabled = dbus_service_enabled;
+        }
+
+        // [stdout] section
+        if let Some(stdout_enabled) = read_config_optional_bool(&ini_config, "stdout", "enabled")? {
+            config.stdout.enabled = stdout_enabled;
+        }
+
+        // [statsd] section
+        if let Some(statsd_enabled) = read_config_optional_bool(&ini_config, "statsd", "enabled")? {
+            config.statsd.enabled = statsd_enabled;
+        }
+        if let Some(address) = ini_config.get("statsd", "address") {
+            config.statsd.address = address;
+        }
+        if let Some(dogstatsd_tags) =
+            read_config_optional_bool(&ini_config, "statsd", "dogstatsd_tags")?
+        {
+            config.statsd.dogstatsd_tags = dogstatsd_tags;
+        }
+        if let Ok(Some(max_packet_bytes)) = ini_config.getuint("statsd", "max_packet_bytes") {
+            config.statsd.max_packet_bytes = max_packet_bytes;
+        }
+
+        // [graphite] section
+        if let Some(graphite_enabled) =
+            read_config_optional_bool(&ini_config, "graphite", "enabled")?
+        {
+            config.graphite.enabled = graphite_enabled;
+        }
+        if let Some(address) = ini_config.get("graphite", "address") {
+            config.graphite.address = address;
+        }
+        if let Ok(Some(max_buffered_lines)) = ini_config.getuint("graphite", "max_buffered_lines") {
+            config.graphite.max_buffered_lines = max_buffered_lines;
+        }
+
+        // [textfile] section
+        if let Some(textfile_enabled) =
+            read_config_optional_bool(&ini_config, "textfile", "enabled")?
+        {
+            config.textfile.enabled = textfile_enabled;
+        }
+        if let Some(path) = ini_config.get("textfile", "path") {
+            config.textfile.path = path.into();
+        }
+
         Ok(config)
     }
 }
@@ -498,6 +896,7 @@ daemon = true
 daemon_stats_refresh_secs = 0
 key_prefix = unittest
 output_format = json-pretty
+prometheus_listen_address = 127.0.0.1:1234
 
 [networkd]
 enabled = true
@@ -513,6 +912,34 @@ bar.service
 [system-state]
 enabled = true
 
+[manager]
+enabled = true
+
+[manager-defaults]
+enabled = true
+
+[tainted]
+enabled = true
+
+[watchdog]
+enabled = true
+
+[oom]
+enabled = true
+all_services = true
+
+[pressure]
+enabled = true
+
+[slices]
+enabled = true
+
+[slices.allowlist]
+system.slice
+
+[slices.blocklist]
+user.slice
+
 [timers]
 enabled = true
 
@@ -530,6 +957,8 @@ ignore_inactive_oneshot_services = true
 unit_files = true
 per_unit_concurrency = 16
 slowest_units_count = 3
+event_tracking = true
+cgroup_fast_path = true
 
 [units.state_stats.allowlist]
 foo.service
@@ -589,8 +1018,50 @@ foo.service
 [boot.blocklist]
 bar.service
 
+[boot-timeline]
+enabled = true
+
+[boot-critical-chain]
+enabled = true
+target = multi-user.target
+
+[boot-history]
+enabled = true
+history_size = 5
+regression_threshold_percent = 20
+history_dir = /tmp/monitord-history-test
+
+[jobs]
+enabled = true
+stuck_job_threshold_secs = 60
+
 [varlink]
 enabled = true
+
+[varlink-server]
+enabled = true
+socket_path = /tmp/io.monitord.Stats
+
+[dbus-service]
+enabled = true
+
+[stdout]
+enabled = false
+
+[statsd]
+enabled = true
+address = statsd.example.com:8125
+dogstatsd_tags = true
+max_packet_bytes = 512
+
+[graphite]
+enabled = true
+address = carbon.example.com:2003
+max_buffered_lines = 1000
+
+[textfile]
+enabled = true
+path = /tmp/monitord.prom
 "###;
 
     const MINIMAL_CONFIG: &str = r###"
@@ -687,6 +1158,9 @@ slowest_units_count = 0
                 key_prefix: String::from("unittest"),
                 output_format: MonitordOutputFormat::JsonPretty,
                 dbus_timeout: 2 as u64,
+                prometheus_listen_address: Some(
+                    "127.0.0.1:1234".parse().expect("Invalid socket address"),
+                ),
             },
             networkd: NetworkdConfig {
                 enabled: true,
@@ -695,6 +1169,20 @@ slowest_units_count = 0
             pid1: Pid1Config { enabled: true },
             services: HashSet::from([String::from("foo.service"), String::from("bar.service")]),
             system_state: SystemStateConfig { enabled: true },
+            manager: ManagerConfig { enabled: true },
+            manager_defaults: ManagerDefaultsConfig { enabled: true },
+            tainted: TaintedConfig { enabled: true },
+            watchdog: WatchdogConfig { enabled: true },
+            oom: OomConfig {
+                enabled: true,
+                all_services: true,
+            },
+            pressure: PressureConfig { enabled: true },
+            slices: SlicesConfig {
+                enabled: true,
+                allowlist: HashSet::from([String::from("system.slice")]),
+                blocklist: HashSet::from([String::from("user.slice")]),
+            },
             timers: TimersConfig {
                 enabled: true,
                 allowlist: HashSet::from([String::from("foo.timer")]),
@@ -710,6 +1198,8 @@ slowest_units_count = 0
                 unit_files: true,
                 per_unit_concurrency: 16,
                 slowest_units_count: 3,
+                event_tracking: true,
+                cgroup_fast_path: true,
             },
             machines: MachinesConfig {
                 enabled: true,
@@ -739,12 +1229,48 @@ slowest_units_count = 0
                 allowlist: HashSet::from([String::from("foo.service")]),
                 blocklist: HashSet::from([String::from("bar.service")]),
             },
+            boot_timeline: BootTimelineConfig { enabled: true },
+            boot_critical_chain: BootCriticalChainConfig {
+                enabled: true,
+                target: "multi-user.target".to_string(),
+            },
+            boot_history: BootHistoryConfig {
+                enabled: true,
+                history_size: 5,
+                regression_threshold_percent: 20,
+                history_dir: "/tmp/monitord-history-test".to_string(),
+            },
             verify: VerifyConfig {
                 enabled: false,
                 allowlist: HashSet::new(),
                 blocklist: HashSet::new(),
             },
+            jobs: JobsConfig {
+                enabled: true,
+                stuck_job_threshold_secs: 60,
+            },
             varlink: VarlinkConfig { enabled: true },
+            varlink_server: VarlinkServerConfig {
+                enabled: true,
+                socket_path: "/tmp/io.monitord.Stats".into(),
+            },
+            dbus_service: DbusServiceConfig { enabled: true },
+            stdout: StdoutConfig { enabled: false },
+            statsd: StatsdConfig {
+                enabled: true,
+                address: String::from("statsd.example.com:8125"),
+                dogstatsd_tags: true,
+                max_packet_bytes: 512,
+            },
+            graphite: GraphiteConfig {
+                enabled: true,
+                address: String::from("carbon.example.com:2003"),
+                max_buffered_lines: 1000,
+            },
+            textfile: TextfileConfig {
+                enabled: true,
+                path: "/tmp/monitord.prom".into(),
+            },
         };
 
         let mut monitord_config = NamedTempFile::new().expect("Unable to make named tempfile");
@@ -762,6 +1288,24 @@ slowest_units_count = 0
         assert_eq!(expected_config, actual_config);
     }
 
+    #[test]
+    fn test_invalid_prometheus_listen_address_returns_error() {
+        let invalid_config =
+            "[monitord]\noutput_format = json\nprometheus_listen_address = localhost\n";
+        let mut monitord_config = NamedTempFile::new().expect("Unable to make named tempfile");
+        monitord_config
+            .write_all(invalid_config.as_bytes())
+            .expect("Unable to write out temp config file");
+
+        let mut ini_config = Ini::new();
+        let _config_map = ini_config
+            .load(monitord_config.path())
+            .expect("Unable to load ini config");
+
+        let result: Result<Config, _> = ini_config.try_into();
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_invalid_config_returns_error() {
         let invalid_config = "[monitord]\ndaemon = notabool\noutput_format = json\n";
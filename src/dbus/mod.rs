@@ -1,3 +1,5 @@
+pub mod properties;
+pub mod zbus_job;
 pub mod zbus_machine;
 pub mod zbus_machines;
 pub mod zbus_networkd;
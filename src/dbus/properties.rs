@@ -0,0 +1,145 @@
+//! # `org.freedesktop.DBus.Properties.GetAll` helpers
+//!
+//! One `GetAll` call returns every property of an interface, where the
+//! generated proxy getters make one D-Bus round trip per property.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use zbus::names::InterfaceName;
+use zbus::zvariant::ObjectPath;
+use zbus::zvariant::OwnedValue;
+use zbus::zvariant::Value;
+
+pub const SERVICE_INTERFACE: &str = "org.freedesktop.systemd1.Service";
+pub const TIMER_INTERFACE: &str = "org.freedesktop.systemd1.Timer";
+pub const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+
+/// Every property of one interface of a unit
+#[derive(Clone, Debug, Default)]
+pub struct Properties {
+    interface: &'static str,
+    values: HashMap<String, OwnedValue>,
+    /// How long the `GetAll` call took
+    pub elapsed_ms: f64,
+}
+
+impl Properties {
+    pub fn new(interface: &'static str, values: HashMap<String, OwnedValue>) -> Self {
+        Properties {
+            interface,
+            values,
+            elapsed_ms: 0.0,
+        }
+    }
+
+    /// Fetch every property of `interface` on the systemd object `object_path`
+    pub async fn get_all(
+        connection: &zbus::Connection,
+        object_path: &ObjectPath<'_>,
+        interface: &'static str,
+    ) -> zbus::Result<Self> {
+        let start = Instant::now();
+        let properties_proxy = zbus::fdo::PropertiesProxy::builder(connection)
+            .destination("org.freedesktop.systemd1")?
+            .path(object_path)?
+            .cache_properties(zbus::proxy::CacheProperties::No)
+            .build()
+            .await?;
+        let values = properties_proxy
+            .get_all(InterfaceName::from_static_str(interface)?)
+            .await?;
+        Ok(Properties {
+            interface,
+            values,
+            elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+        })
+    }
+
+    /// Decode the property `name`. A property the running systemd doesn't
+    /// have is an `UnknownProperty` error, the same as from a proxy getter.
+    pub fn get<'a, T>(&'a self, name: &str) -> zbus::Result<T>
+    where
+        T: TryFrom<&'a Value<'a>>,
+        <T as TryFrom<&'a Value<'a>>>::Error: Into<zbus::zvariant::Error>,
+    {
+        match self.values.get(name) {
+            Some(value) => Ok(value.downcast_ref::<T>()?),
+            None => Err(zbus::Error::FDO(Box::new(
+                zbus::fdo::Error::UnknownProperty(format!("{} has no {}", self.interface, name)),
+            ))),
+        }
+    }
+}
+
+/// Number and summed duration of `GetAll` calls
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GetAllTimings {
+    pub calls: u64,
+    pub elapsed_ms: f64,
+}
+
+impl GetAllTimings {
+    pub fn record(&mut self, properties: &Properties) {
+        self.calls += 1;
+        self.elapsed_ms += properties.elapsed_ms;
+    }
+
+    pub fn add(&mut self, other: &GetAllTimings) {
+        self.calls += other.calls;
+        self.elapsed_ms += other.elapsed_ms;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_properties_get() {
+        let properties = Properties::new(
+            UNIT_INTERFACE,
+            HashMap::from([
+                (
+                    String::from("ActiveEnterTimestamp"),
+                    OwnedValue::from(42_u64),
+                ),
+                (
+                    String::from("Id"),
+                    OwnedValue::try_from(Value::from("foo.service")).expect("owned str"),
+                ),
+            ]),
+        );
+        assert_eq!(42, properties.get::<u64>("ActiveEnterTimestamp").unwrap());
+        assert_eq!(
+            "foo.service",
+            properties.get::<String>("Id").unwrap().as_str()
+        );
+        // Wrong type
+        assert!(properties.get::<bool>("ActiveEnterTimestamp").is_err());
+        match properties.get::<u64>("MemoryPeak") {
+            Err(zbus::Error::FDO(err)) => {
+                assert!(matches!(*err, zbus::fdo::Error::UnknownProperty(_)))
+            }
+            other => panic!("Expected UnknownProperty, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_get_all_timings() {
+        let mut properties = Properties::new(TIMER_INTERFACE, HashMap::new());
+        properties.elapsed_ms = 1.5;
+        let mut timings = GetAllTimings::default();
+        timings.record(&properties);
+        timings.record(&properties);
+        let mut total = GetAllTimings::default();
+        total.add(&timings);
+        assert_eq!(
+            GetAllTimings {
+                calls: 2,
+                elapsed_ms: 3.0
+            },
+            total
+        );
+    }
+}
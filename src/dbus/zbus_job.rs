@@ -0,0 +1,77 @@
+//! # D-Bus interface proxy for: `org.freedesktop.systemd1.Job`
+//!
+//! This code was generated by `zbus-xmlgen` `5.1.0` from D-Bus introspection data.
+//! Source: `Interface '/org/freedesktop/systemd1/job/1234' from service 'org.freedesktop.systemd1' on system bus`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//! This type implements the [D-Bus standard interfaces], (`org.freedesktop.DBus.*`) for which the
+//! following zbus API can be used:
+//!
+//! * [`zbus::fdo::PeerProxy`]
+//! * [`zbus::fdo::IntrospectableProxy`]
+//! * [`zbus::fdo::PropertiesProxy`]
+//!
+//! Consequently `zbus-xmlgen` did not generate code for the above interfaces.
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+#![allow(warnings)]
+#![allow(clippy)]
+use zbus::proxy;
+#[proxy(
+    interface = "org.freedesktop.systemd1.Job",
+    default_service = "org.freedesktop.systemd1",
+    default_path = "/org/freedesktop/systemd1/job/1234"
+)]
+pub trait Job {
+    /// Cancel method
+    fn cancel(&self) -> zbus::Result<()>;
+
+    /// GetAfter method
+    fn get_after(
+        &self,
+    ) -> zbus::Result<
+        Vec<(
+            u32,
+            String,
+            String,
+            String,
+            zbus::zvariant::OwnedObjectPath,
+            zbus::zvariant::OwnedObjectPath,
+        )>,
+    >;
+
+    /// GetBefore method
+    fn get_before(
+        &self,
+    ) -> zbus::Result<
+        Vec<(
+            u32,
+            String,
+            String,
+            String,
+            zbus::zvariant::OwnedObjectPath,
+            zbus::zvariant::OwnedObjectPath,
+        )>,
+    >;
+
+    /// Id property
+    #[zbus(property)]
+    fn id(&self) -> zbus::Result<u32>;
+
+    /// JobType property
+    #[zbus(property)]
+    fn job_type(&self) -> zbus::Result<String>;
+
+    /// State property
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<String>;
+
+    /// Unit property
+    #[zbus(property)]
+    fn unit(&self) -> zbus::Result<(String, zbus::zvariant::OwnedObjectPath)>;
+}
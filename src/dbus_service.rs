@@ -0,0 +1,193 @@
+//! # dbus_service module
+//!
+//! Publish a summary of the latest `MonitordStats` on the system bus as
+//! `io.monitord1` in daemon mode. Properties emit `PropertiesChanged` when
+//! their value changes and `StatsUpdated` is emitted after every run so
+//! tooling can react to health changes without polling.
+//!
+//! ```text
+//! busctl get-property io.monitord1 /io/monitord1 io.monitord1.Stats SystemState
+//! busctl monitor io.monitord1
+//! ```
+
+use std::collections::HashMap;
+
+use tracing::debug;
+use zbus::object_server::InterfaceRef;
+use zbus::object_server::SignalEmitter;
+
+use crate::MonitordStats;
+
+pub const BUS_NAME: &str = "io.monitord1";
+pub const OBJECT_PATH: &str = "/io/monitord1";
+
+/// Values exported as properties on `io.monitord1.Stats`
+#[derive(Clone, Debug, Default, PartialEq)]
+struct BusStats {
+    system_state: String,
+    failed_units: u64,
+    unit_health: HashMap<String, bool>,
+    version: String,
+    stat_collection_run_time_ms: f64,
+}
+
+impl From<&MonitordStats> for BusStats {
+    fn from(stats: &MonitordStats) -> Self {
+        BusStats {
+            system_state: stats.system_state.to_string(),
+            failed_units: stats.units.failed_units,
+            unit_health: stats
+                .units
+                .unit_states
+                .iter()
+                .map(|(unit, state)| (unit.clone(), !state.unhealthy))
+                .collect(),
+            version: stats.version.to_string(),
+            stat_collection_run_time_ms: stats.stat_collection_run_time_ms,
+        }
+    }
+}
+
+/// Object exported at `/io/monitord1`
+#[derive(Debug, Default)]
+pub struct MonitordBusObject {
+    stats: BusStats,
+}
+
+#[zbus::interface(name = "io.monitord1.Stats")]
+impl MonitordBusObject {
+    /// systemd manager SystemState (e.g. running, degraded)
+    #[zbus(property)]
+    fn system_state(&self) -> String {
+        self.stats.system_state.clone()
+    }
+
+    /// Number of units in the failed state
+    #[zbus(property)]
+    fn failed_units(&self) -> u64 {
+        self.stats.failed_units
+    }
+
+    /// Unit name to healthy (true) or unhealthy (false) for units with state stats
+    #[zbus(property)]
+    fn unit_health(&self) -> HashMap<String, bool> {
+        self.stats.unit_health.clone()
+    }
+
+    /// Installed systemd version
+    #[zbus(property)]
+    fn version(&self) -> String {
+        self.stats.version.clone()
+    }
+
+    /// Duration of the last stat collection run in milliseconds
+    #[zbus(property)]
+    fn stat_collection_run_time_ms(&self) -> f64 {
+        self.stats.stat_collection_run_time_ms
+    }
+
+    /// Emitted after every stat collection run
+    #[zbus(signal)]
+    async fn stats_updated(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+}
+
+/// Handle used by the collection loop to update the exported object
+pub struct DbusServicePublisher {
+    iface_ref: InterfaceRef<MonitordBusObject>,
+}
+
+impl DbusServicePublisher {
+    /// Update properties from a finished collection run, signalling what changed
+    pub async fn publish(&self, stats: &MonitordStats) -> zbus::Result<()> {
+        let new_stats = BusStats::from(stats);
+        let emitter = self.iface_ref.signal_emitter();
+        {
+            let mut iface = self.iface_ref.get_mut().await;
+            let old_stats = std::mem::replace(&mut iface.stats, new_stats);
+            if old_stats.system_state != iface.stats.system_state {
+                iface.system_state_changed(emitter).await?;
+            }
+            if old_stats.failed_units != iface.stats.failed_units {
+                iface.failed_units_changed(emitter).await?;
+            }
+            if old_stats.unit_health != iface.stats.unit_health {
+                iface.unit_health_changed(emitter).await?;
+            }
+            if old_stats.version != iface.stats.version {
+                iface.version_changed(emitter).await?;
+            }
+            // Changes every run so only sent via StatsUpdated / Get
+        }
+        MonitordBusObject::stats_updated(emitter).await?;
+        debug!("Published stats on D-Bus as {}", BUS_NAME);
+        Ok(())
+    }
+}
+
+/// Export the stats object on `connection` and claim `io.monitord1`
+pub async fn register(connection: &zbus::Connection) -> zbus::Result<DbusServicePublisher> {
+    let exported = connection
+        .object_server()
+        .at(OBJECT_PATH, MonitordBusObject::default())
+        .await?;
+    if !exported {
+        // A previous stat_collector on this connection already exported it,
+        // keep publishing through that one
+        debug!(
+            "{} already exported on {}, reusing it",
+            BUS_NAME, OBJECT_PATH
+        );
+    }
+    connection.request_name(BUS_NAME).await?;
+    let iface_ref = connection
+        .object_server()
+        .interface::<_, MonitordBusObject>(OBJECT_PATH)
+        .await?;
+    Ok(DbusServicePublisher { iface_ref })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::SystemdSystemState;
+    use crate::units::UnitStates;
+
+    #[test]
+    fn test_bus_stats_from_monitord_stats() {
+        let mut stats = MonitordStats {
+            system_state: SystemdSystemState::degraded,
+            stat_collection_run_time_ms: 69.0,
+            version: String::from("255.7-1.fc40")
+                .try_into()
+                .expect("Unable to make SystemdVersion struct"),
+            ..Default::default()
+        };
+        stats.units.failed_units = 1;
+        stats.units.unit_states.insert(
+            String::from("foo.service"),
+            UnitStates {
+                unhealthy: true,
+                ..Default::default()
+            },
+        );
+        stats
+            .units
+            .unit_states
+            .insert(String::from("bar.service"), UnitStates::default());
+
+        let bus_stats = BusStats::from(&stats);
+        assert_eq!(
+            BusStats {
+                system_state: String::from("degraded"),
+                failed_units: 1,
+                unit_health: HashMap::from([
+                    (String::from("foo.service"), false),
+                    (String::from("bar.service"), true),
+                ]),
+                version: String::from("255.7-1.fc40"),
+                stat_collection_run_time_ms: 69.0,
+            },
+            bus_stats
+        );
+    }
+}
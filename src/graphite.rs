@@ -0,0 +1,275 @@
+//! # graphite module
+//!
+//! Ship the dotted `json::flatten` keys to a carbon relay using the Graphite
+//! plaintext protocol (`key value timestamp\n`) over TCP. Lines from runs that
+//! happen while the relay is unreachable are kept in a bounded buffer and sent
+//! once a reconnect succeeds.
+//! Ref: <https://graphite.readthedocs.io/en/latest/feeding-carbon.html>
+
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+use crate::config::GraphiteConfig;
+use crate::MonitordStats;
+
+#[derive(Error, Debug)]
+pub enum MonitordGraphiteError {
+    #[error("Graphite I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Timed out connecting to Graphite at '{0}'")]
+    ConnectTimeout(String),
+    #[error("Timed out writing to Graphite at '{0}'")]
+    WriteTimeout(String),
+    #[error("Graphite relay unavailable, next reconnect in {0:?}; {1} lines buffered")]
+    Backoff(Duration, usize),
+}
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// A stalled relay with a full TCP window would otherwise block the sink forever
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Graphite paths can not contain whitespace
+fn sanitize_key(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect()
+}
+
+/// Build plaintext protocol lines for every numeric flat key/value
+fn graphite_lines(
+    flat_stats: &BTreeMap<String, serde_json::Value>,
+    timestamp_secs: u64,
+) -> Vec<String> {
+    flat_stats
+        .iter()
+        .filter(|(_, value)| value.is_number())
+        .map(|(key, value)| format!("{} {} {}\n", sanitize_key(key), value, timestamp_secs))
+        .collect()
+}
+
+/// Persistent connection to a carbon relay with reconnect backoff
+pub struct GraphiteSink {
+    address: String,
+    key_prefix: String,
+    stream: Option<TcpStream>,
+    buffer: VecDeque<String>,
+    max_buffered_lines: usize,
+    backoff: Duration,
+    next_connect_attempt: Option<Instant>,
+}
+
+impl GraphiteSink {
+    pub fn new(config: &GraphiteConfig, key_prefix: &str) -> Self {
+        Self {
+            address: config.address.clone(),
+            key_prefix: key_prefix.to_string(),
+            stream: None,
+            buffer: VecDeque::new(),
+            max_buffered_lines: config.max_buffered_lines as usize,
+            backoff: INITIAL_BACKOFF,
+            next_connect_attempt: None,
+        }
+    }
+
+    /// Add lines to the buffer dropping the oldest once full
+    fn buffer_lines(&mut self, lines: Vec<String>) {
+        self.buffer.extend(lines);
+        let overflow = self.buffer.len().saturating_sub(self.max_buffered_lines);
+        if overflow > 0 {
+            warn!(
+                "Graphite buffer full, dropping {} oldest lines (max {})",
+                overflow, self.max_buffered_lines
+            );
+            self.buffer.drain(..overflow);
+        }
+    }
+
+    fn schedule_reconnect(&mut self) {
+        self.stream = None;
+        self.next_connect_attempt = Some(Instant::now() + self.backoff);
+        self.backoff = std::cmp::min(self.backoff * 2, MAX_BACKOFF);
+    }
+
+    async fn connect(&mut self) -> Result<(), MonitordGraphiteError> {
+        if let Some(next_attempt) = self.next_connect_attempt {
+            let now = Instant::now();
+            if now < next_attempt {
+                return Err(MonitordGraphiteError::Backoff(
+                    next_attempt - now,
+                    self.buffer.len(),
+                ));
+            }
+        }
+        match tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(&self.address)).await {
+            Ok(Ok(stream)) => {
+                info!("Connected to Graphite at {}", self.address);
+                self.stream = Some(stream);
+                Ok(())
+            }
+            Ok(Err(err)) => {
+                self.schedule_reconnect();
+                Err(err.into())
+            }
+            Err(_) => {
+                self.schedule_reconnect();
+                Err(MonitordGraphiteError::ConnectTimeout(self.address.clone()))
+            }
+        }
+    }
+
+    /// Buffer this run's lines then try to flush everything buffered
+    pub async fn send(&mut self, stats: &MonitordStats) -> Result<(), MonitordGraphiteError> {
+        let timestamp_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let flat_stats = crate::json::flatten_stats(stats, &self.key_prefix);
+        self.buffer_lines(graphite_lines(&flat_stats, timestamp_secs));
+
+        if self.stream.is_none() {
+            self.connect().await?;
+        }
+        let payload: String = self.buffer.iter().map(|l| l.as_str()).collect();
+        let Some(stream) = self.stream.as_mut() else {
+            return Ok(());
+        };
+        // On failure keep everything buffered; carbon treats a resent
+        // key + timestamp as an overwrite so partial resends are harmless
+        let write = async {
+            stream.write_all(payload.as_bytes()).await?;
+            stream.flush().await
+        };
+        match tokio::time::timeout(WRITE_TIMEOUT, write).await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => {
+                self.schedule_reconnect();
+                return Err(err.into());
+            }
+            Err(_) => {
+                self.schedule_reconnect();
+                return Err(MonitordGraphiteError::WriteTimeout(self.address.clone()));
+            }
+        }
+        debug!("Sent {} lines to Graphite", self.buffer.len());
+        self.buffer.clear();
+        self.backoff = INITIAL_BACKOFF;
+        self.next_connect_attempt = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    fn return_monitord_stats() -> MonitordStats {
+        MonitordStats {
+            stat_collection_run_time_ms: 69.0,
+            version: String::from("255.7-1.fc40")
+                .try_into()
+                .expect("Unable to make SystemdVersion struct"),
+            ..Default::default()
+        }
+    }
+
+    fn graphite_config(address: String, max_buffered_lines: u64) -> GraphiteConfig {
+        GraphiteConfig {
+            enabled: true,
+            address,
+            max_buffered_lines,
+        }
+    }
+
+    #[test]
+    fn test_graphite_lines() {
+        let flat_stats = BTreeMap::from([
+            (String::from("a.b c"), serde_json::Value::from(1)),
+            (String::from("a.float"), serde_json::Value::from(0.5)),
+            (String::from("version"), serde_json::Value::from("255")),
+        ]);
+        assert_eq!(
+            vec![
+                String::from("a.b_c 1 1700000000\n"),
+                String::from("a.float 0.5 1700000000\n"),
+            ],
+            graphite_lines(&flat_stats, 1_700_000_000)
+        );
+    }
+
+    #[test]
+    fn test_buffer_is_bounded() {
+        let mut sink =
+            GraphiteSink::new(&graphite_config(String::from("127.0.0.1:1"), 3), "monitord");
+        sink.buffer_lines(vec![
+            String::from("a"),
+            String::from("b"),
+            String::from("c"),
+            String::from("d"),
+        ]);
+        assert_eq!(
+            VecDeque::from([String::from("b"), String::from("c"), String::from("d")]),
+            sink.buffer
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_and_reconnect() {
+        // Grab a free port then close it so the first connect is refused
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Unable to bind");
+        let address = listener.local_addr().expect("No local address");
+        drop(listener);
+
+        let mut sink = GraphiteSink::new(&graphite_config(address.to_string(), 1000), "monitord");
+        let stats = return_monitord_stats();
+        assert!(sink.send(&stats).await.is_err());
+        assert!(!sink.buffer.is_empty());
+        let buffered_lines = sink.buffer.len();
+
+        // Still backing off so no connection attempt is made
+        match sink.send(&stats).await {
+            Err(MonitordGraphiteError::Backoff(_, lines)) => {
+                assert_eq!(buffered_lines * 2, lines)
+            }
+            other => panic!("Expected backoff error, got {:?}", other),
+        }
+
+        let listener = TcpListener::bind(address).await.expect("Unable to re-bind");
+        sink.next_connect_attempt = Some(Instant::now());
+        sink.send(&stats)
+            .await
+            .expect("Send after relay came back failed");
+        assert!(sink.buffer.is_empty());
+        assert_eq!(INITIAL_BACKOFF, sink.backoff);
+        drop(sink);
+
+        let (mut conn, _) = listener.accept().await.expect("No connection");
+        let mut received = String::new();
+        conn.read_to_string(&mut received)
+            .await
+            .expect("Unable to read");
+        let run_time_lines = received
+            .lines()
+            .filter(|l| l.starts_with("monitord.stat_collection_run_time_ms 69.0 "))
+            .count();
+        assert_eq!(3, run_time_lines);
+        assert!(!received.contains("monitord.version"));
+    }
+}
@@ -0,0 +1,261 @@
+//! # influx module
+//!
+//! Render `MonitordStats` as InfluxDB line protocol. Each subsystem is its own
+//! measurement and unit, interface and machine names are tags rather than being
+//! baked into the key like `json::flatten` does.
+//! Ref: <https://docs.influxdata.com/influxdb/v2/reference/syntax/line-protocol/>
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::json::gen_base_metric_key;
+use crate::metrics::MetricKind;
+use crate::metrics::MetricValue;
+use crate::metrics::MetricWriter;
+use crate::MonitordStats;
+
+/// Integers are always written as signed (`i`) so a field never changes type
+/// between runs; unsigned values above `i64::MAX` (e.g. unlimited memory)
+/// saturate.
+fn render_value(value: &MetricValue) -> Option<String> {
+    match value {
+        MetricValue::Boolean(b) => Some(b.to_string()),
+        // NaN and infinities are not representable in line protocol
+        MetricValue::Float(f) if !f.is_finite() => None,
+        MetricValue::Float(f) => Some(f.to_string()),
+        MetricValue::Integer(i) => Some(format!("{i}i")),
+        MetricValue::Unsigned(u) => Some(format!("{}i", i64::try_from(*u).unwrap_or(i64::MAX))),
+        MetricValue::String(s) => Some(format!("\"{}\"", escape_string_field(s))),
+    }
+}
+
+/// A series is a measurement plus its sorted tag set
+type SeriesKey = (String, Vec<(String, String)>);
+
+/// Points keyed by measurement and sorted tag set so fields sharing both end up
+/// on the same line. Measurements are `<key_prefix>.<subsystem>`.
+struct PointSet {
+    key_prefix: String,
+    points: BTreeMap<SeriesKey, BTreeMap<String, MetricValue>>,
+}
+
+impl PointSet {
+    fn new(key_prefix: &str) -> Self {
+        PointSet {
+            key_prefix: key_prefix.to_string(),
+            points: BTreeMap::new(),
+        }
+    }
+
+    fn render(&self, timestamp_ns: u128) -> String {
+        let mut output = String::new();
+        for ((measurement, tags), fields) in &self.points {
+            let rendered_fields: Vec<String> = fields
+                .iter()
+                .filter_map(|(k, v)| Some(format!("{}={}", escape_key(k), render_value(v)?)))
+                .collect();
+            // A point without fields is invalid line protocol
+            if rendered_fields.is_empty() {
+                continue;
+            }
+            output.push_str(&escape_measurement(measurement));
+            for (k, v) in tags {
+                // Empty tag values are invalid; drop the tag
+                if v.is_empty() {
+                    continue;
+                }
+                let _ = write!(output, ",{}={}", escape_key(k), escape_key(v));
+            }
+            let _ = writeln!(output, " {} {}", rendered_fields.join(","), timestamp_ns);
+        }
+        output
+    }
+}
+
+impl MetricWriter for PointSet {
+    fn push(
+        &mut self,
+        subsystem: &str,
+        field: &str,
+        _help: &str,
+        _kind: MetricKind,
+        labels: &[(&str, &str)],
+        value: MetricValue,
+    ) {
+        let mut sorted_tags: Vec<(String, String)> = labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        sorted_tags.sort();
+        self.points
+            .entry((
+                gen_base_metric_key(&self.key_prefix, subsystem),
+                sorted_tags,
+            ))
+            .or_default()
+            .insert(field.to_string(), value);
+    }
+
+    /// Strings are plain string fields
+    fn push_info(
+        &mut self,
+        subsystem: &str,
+        field: &str,
+        help: &str,
+        labels: &[(&str, &str)],
+        value: &str,
+    ) {
+        self.push(
+            subsystem,
+            field,
+            help,
+            MetricKind::Gauge,
+            labels,
+            MetricValue::String(value.to_string()),
+        );
+    }
+}
+
+fn escape_measurement(measurement: &str) -> String {
+    measurement
+        .replace('\\', r"\\")
+        .replace(',', r"\,")
+        .replace(' ', r"\ ")
+        .replace('\n', r"\n")
+}
+
+/// Escaping shared by tag keys, tag values and field keys
+fn escape_key(key: &str) -> String {
+    escape_measurement(key).replace('=', r"\=")
+}
+
+fn escape_string_field(value: &str) -> String {
+    value.replace('\\', r"\\").replace('"', "\\\"")
+}
+
+/// Render all stats as line protocol with every point stamped `timestamp_ns`
+pub fn render(stats: &MonitordStats, key_prefix: &str, timestamp_ns: u128) -> String {
+    let mut points = PointSet::new(key_prefix);
+    crate::metrics::walk(&mut points, stats);
+    points.render(timestamp_ns)
+}
+
+/// Take the standard returned structs and render them as line protocol stamped now
+pub fn to_line_protocol(stats: &MonitordStats, key_prefix: &str) -> String {
+    let timestamp_ns = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    render(stats, key_prefix, timestamp_ns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networkd;
+    use crate::units;
+
+    const TIMESTAMP_NS: u128 = 1_700_000_000_000_000_000;
+
+    fn return_monitord_stats() -> MonitordStats {
+        let mut stats = MonitordStats {
+            system_state: crate::system::SystemdSystemState::running,
+            version: String::from("255.7-1.fc40")
+                .try_into()
+                .expect("Unable to make SystemdVersion struct"),
+            stat_collection_run_time_ms: 69.5,
+            ..Default::default()
+        };
+        stats.networkd.managed_interfaces = 1;
+        stats
+            .networkd
+            .interfaces_state
+            .push(networkd::InterfaceState {
+                name: "eth0".to_string(),
+                oper_state: networkd::OperState::routable,
+                ..Default::default()
+            });
+        stats.units.service_stats.insert(
+            String::from("unittest.service"),
+            units::ServiceStats {
+                status_errno: -69,
+                memory_available: u64::MAX,
+                ..Default::default()
+            },
+        );
+        let mut machine = crate::MachineStats::default();
+        machine.units.unit_states.insert(
+            String::from("foo bar,baz.service"),
+            units::UnitStates {
+                unhealthy: true,
+                ..Default::default()
+            },
+        );
+        stats.machines.insert(String::from("foo"), machine);
+        stats.extra.insert(
+            String::from("custom"),
+            serde_json::json!({"count": 5, "list": [1], "name": "bar"}),
+        );
+        stats
+            .extra
+            .insert(String::from("plain"), serde_json::Value::from(2.5));
+        stats
+    }
+
+    #[test]
+    fn test_render() {
+        let output = render(&return_monitord_stats(), "monitord", TIMESTAMP_NS);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert!(lines.contains(&"monitord.stat_collection run_time_ms=69.5 1700000000000000000"));
+        assert!(lines
+            .contains(&"monitord.system state=3i,version=\"255.7-1.fc40\" 1700000000000000000"));
+        let eth0 = lines
+            .iter()
+            .find(|l| l.starts_with("monitord.networkd,interface=eth0 "))
+            .expect("No eth0 networkd point");
+        assert!(eth0.contains("oper_state=9i"));
+        let service = lines
+            .iter()
+            .find(|l| l.starts_with("monitord.service,unit=unittest.service "))
+            .expect("No unittest.service point");
+        assert!(service.contains("status_errno=-69i"));
+        assert!(service.contains(&format!("memory_available={}i", i64::MAX)));
+        assert!(service.ends_with(" 1700000000000000000"));
+        let unit_state = lines
+            .iter()
+            .find(|l| l.starts_with(r"monitord.unit,machine=foo,unit=foo\ bar\,baz.service "))
+            .expect("No escaped machine unit state point");
+        assert!(unit_state.contains("unhealthy=true"));
+        assert!(lines.contains(
+            &"monitord.extra,collector=custom count=5i,list.0=1i,name=\"bar\" 1700000000000000000"
+        ));
+        assert!(lines.contains(&"monitord.extra,collector=plain value=2.5 1700000000000000000"));
+    }
+
+    #[test]
+    fn test_render_one_line_per_series() {
+        let output = render(&return_monitord_stats(), "", TIMESTAMP_NS);
+        let system_lines = output.lines().filter(|l| l.starts_with("system ")).count();
+        assert_eq!(1, system_lines);
+        let machine_system_lines = output
+            .lines()
+            .filter(|l| l.starts_with("system,machine=foo "))
+            .count();
+        assert_eq!(1, machine_system_lines);
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(r"a\ b\,c", escape_measurement("a b,c"));
+        assert_eq!(r"a\=b\ c", escape_key("a=b c"));
+        assert_eq!(r#"a\"b\\"#, escape_string_field("a\"b\\"));
+        assert_eq!(None, render_value(&MetricValue::Float(f64::NAN)));
+        assert_eq!(
+            Some(format!("{}i", i64::MAX)),
+            render_value(&MetricValue::from(u64::MAX))
+        );
+    }
+}
@@ -0,0 +1,417 @@
+//! # jobs module
+//!
+//! systemd job lifecycle stats. In daemon mode `JobNew` and `JobRemoved`
+//! signals are followed to time every job and count how it finished, per job
+//! type (start, stop, restart ...). Every run `ListJobs` is also checked for
+//! jobs queued longer than `stuck_job_threshold_secs`.
+//!
+//! `JobNew` doesn't carry the job type, so it is looked up on the job object
+//! without holding up the following signals. A lookup that answers after
+//! `JobRemoved` still sets the type of the finished job. Jobs systemd already
+//! dropped before the lookup reached it are counted as `unknown`.
+//! Ref: <https://www.freedesktop.org/software/systemd/man/org.freedesktop.systemd1.html#Job%20Objects>
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures_util::stream::StreamExt;
+use tokio::sync::Mutex;
+use tokio::sync::RwLock;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+use crate::dbus::zbus_job::JobProxy;
+use crate::dbus::zbus_systemd::ManagerProxy;
+use crate::MachineStats;
+
+/// Used for jobs whose type lookup failed, usually because systemd had
+/// already dropped the job
+pub const UNKNOWN_JOB_TYPE: &str = "unknown";
+
+/// Finished jobs by `JobRemoved` result
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct JobResultCounts {
+    pub canceled: u64,
+    pub dependency: u64,
+    pub done: u64,
+    pub failed: u64,
+    pub skipped: u64,
+    pub timeout: u64,
+    /// Any other result (e.g. invalid, collected, once)
+    pub other: u64,
+}
+
+impl JobResultCounts {
+    pub fn record(&mut self, result: &str) {
+        match result {
+            "canceled" => self.canceled += 1,
+            "dependency" => self.dependency += 1,
+            "done" => self.done += 1,
+            "failed" => self.failed += 1,
+            "skipped" => self.skipped += 1,
+            "timeout" => self.timeout += 1,
+            _ => self.other += 1,
+        }
+    }
+}
+
+/// Results and durations of finished jobs of one job type
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct JobTypeStats {
+    pub results: JobResultCounts,
+    /// Duration percentiles of jobs seen from JobNew to JobRemoved
+    pub duration_ms_p50: f64,
+    pub duration_ms_p90: f64,
+    pub duration_ms_p99: f64,
+    pub duration_ms_max: f64,
+}
+
+/// A job queued for longer than `stuck_job_threshold_secs`
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct StuckJob {
+    pub id: u32,
+    pub unit: String,
+    pub job_type: String,
+    /// waiting or running
+    pub state: String,
+    /// Seconds since monitord first saw the job
+    pub age_secs: u64,
+}
+
+/// Job stats since the previous run
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct JobStats {
+    /// Finished jobs of all types by result
+    pub results: JobResultCounts,
+    /// Finished jobs keyed by job type
+    pub by_type: HashMap<String, JobTypeStats>,
+    /// Jobs in the systemd job queue at collection time
+    pub queued: u64,
+    /// Queued jobs older than the threshold, oldest first
+    pub stuck_jobs: Vec<StuckJob>,
+}
+
+#[derive(Debug)]
+struct RunningJob {
+    job_type: Option<String>,
+    started: Instant,
+}
+
+#[derive(Debug)]
+struct FinishedJob {
+    id: u32,
+    /// None until the type lookup answers
+    job_type: Option<String>,
+    result: String,
+    duration: Option<Duration>,
+}
+
+/// Job state kept between runs
+#[derive(Debug, Default)]
+pub struct JobTracker {
+    running: HashMap<u32, RunningJob>,
+    finished: Vec<FinishedJob>,
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl JobTracker {
+    pub fn record_job_new(&mut self, id: u32, now: Instant) {
+        self.running.entry(id).or_insert(RunningJob {
+            job_type: None,
+            started: now,
+        });
+    }
+
+    /// Set the job type looked up after JobNew, whether or not the job has
+    /// finished since
+    pub fn record_job_type(&mut self, id: u32, job_type: &str) {
+        if let Some(job) = self.running.get_mut(&id) {
+            job.job_type = Some(job_type.to_string());
+        } else if let Some(job) = self
+            .finished
+            .iter_mut()
+            .rev()
+            .find(|j| j.id == id && j.job_type.is_none())
+        {
+            job.job_type = Some(job_type.to_string());
+        }
+    }
+
+    /// Jobs we did not see start are counted without a duration
+    pub fn record_job_removed(&mut self, id: u32, result: &str, now: Instant) {
+        let job = self.running.remove(&id);
+        self.finished.push(FinishedJob {
+            id,
+            job_type: job.as_ref().and_then(|j| j.job_type.clone()),
+            result: result.to_string(),
+            duration: job.map(|j| now.saturating_duration_since(j.started)),
+        });
+    }
+
+    /// Build stats from jobs finished since the last call and the current job
+    /// queue from ListJobs, as (id, unit, job type, state) fetched at `listed_at`
+    pub fn take_stats(
+        &mut self,
+        queue: &[(u32, String, String, String)],
+        listed_at: Instant,
+        stuck_job_threshold: Duration,
+    ) -> JobStats {
+        let mut stats = JobStats {
+            queued: queue.len() as u64,
+            ..Default::default()
+        };
+
+        let mut durations: HashMap<String, Vec<f64>> = HashMap::new();
+        for job in self.finished.drain(..) {
+            let job_type = job.job_type.unwrap_or_else(|| UNKNOWN_JOB_TYPE.to_string());
+            stats.results.record(&job.result);
+            let type_stats = stats.by_type.entry(job_type.clone()).or_default();
+            type_stats.results.record(&job.result);
+            if let Some(duration) = job.duration {
+                durations
+                    .entry(job_type)
+                    .or_default()
+                    .push(duration.as_secs_f64() * 1000.0);
+            }
+        }
+        for (job_type, mut type_durations) in durations {
+            type_durations.sort_by(f64::total_cmp);
+            if let Some(type_stats) = stats.by_type.get_mut(&job_type) {
+                type_stats.duration_ms_p50 = percentile(&type_durations, 50.0);
+                type_stats.duration_ms_p90 = percentile(&type_durations, 90.0);
+                type_stats.duration_ms_p99 = percentile(&type_durations, 99.0);
+                type_stats.duration_ms_max = percentile(&type_durations, 100.0);
+            }
+        }
+
+        // Forget jobs that left the queue without us seeing JobRemoved. Jobs
+        // that started after ListJobs was called can't be in `queue` yet.
+        self.running.retain(|id, job| {
+            job.started > listed_at || queue.iter().any(|(queued_id, ..)| queued_id == id)
+        });
+        for (id, unit, job_type, state) in queue {
+            let job = self.running.entry(*id).or_insert(RunningJob {
+                job_type: None,
+                started: listed_at,
+            });
+            job.job_type.get_or_insert_with(|| job_type.clone());
+            let age = listed_at.saturating_duration_since(job.started);
+            if age >= stuck_job_threshold {
+                stats.stuck_jobs.push(StuckJob {
+                    id: *id,
+                    unit: unit.clone(),
+                    job_type: job_type.clone(),
+                    state: state.clone(),
+                    age_secs: age.as_secs(),
+                });
+            }
+        }
+        stats
+            .stuck_jobs
+            .sort_by(|a, b| b.age_secs.cmp(&a.age_secs).then(a.id.cmp(&b.id)));
+        stats
+    }
+}
+
+/// Look up the type of a new job. Short jobs are often gone before we ask.
+async fn get_job_type(
+    connection: &zbus::Connection,
+    job_path: zbus::zvariant::OwnedObjectPath,
+) -> zbus::Result<String> {
+    let job = JobProxy::builder(connection)
+        .path(job_path)?
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    job.job_type().await
+}
+
+/// Subscribe to systemd job signals and update `tracker` until the bus goes away.
+/// A signal that can't be decoded is logged and skipped.
+pub async fn track_jobs(
+    connection: zbus::Connection,
+    tracker: Arc<Mutex<JobTracker>>,
+) -> anyhow::Result<()> {
+    let manager = ManagerProxy::builder(&connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let mut job_new = manager.receive_job_new().await?;
+    let mut job_removed = manager.receive_job_removed().await?;
+    crate::unit_events::subscribe(&manager).await?;
+    info!("Tracking systemd jobs");
+
+    loop {
+        tokio::select! {
+            Some(signal) = job_new.next() => {
+                let now = Instant::now();
+                let args = match signal.args() {
+                    Ok(args) => args,
+                    Err(err) => {
+                        warn!("Unable to decode JobNew: {:?}", err);
+                        continue;
+                    }
+                };
+                let id = args.id;
+                let job_path = zbus::zvariant::OwnedObjectPath::from(args.job.to_owned());
+                tracker.lock().await.record_job_new(id, now);
+                // Don't hold up timestamps of following signals on the lookup
+                let connection = connection.clone();
+                let tracker = tracker.clone();
+                tokio::spawn(async move {
+                    match get_job_type(&connection, job_path).await {
+                        Ok(job_type) => tracker.lock().await.record_job_type(id, &job_type),
+                        Err(err) => debug!("Unable to get type of job {}: {:?}", id, err),
+                    }
+                });
+            }
+            Some(signal) = job_removed.next() => {
+                let now = Instant::now();
+                match signal.args() {
+                    Ok(args) => tracker.lock().await.record_job_removed(args.id, args.result, now),
+                    Err(err) => warn!("Unable to decode JobRemoved: {:?}", err),
+                }
+            }
+            else => break,
+        }
+    }
+    Err(anyhow::anyhow!("systemd job signal streams closed"))
+}
+
+/// Fetch the job queue and store job stats since the previous run
+pub async fn update_job_stats(
+    connection: zbus::Connection,
+    tracker: Arc<Mutex<JobTracker>>,
+    stuck_job_threshold: Duration,
+    locked_machine_stats: Arc<RwLock<MachineStats>>,
+) -> anyhow::Result<()> {
+    let manager = ManagerProxy::builder(&connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let listed_at = Instant::now();
+    let queue: Vec<(u32, String, String, String)> = manager
+        .list_jobs()
+        .await?
+        .into_iter()
+        .map(|(id, unit, job_type, state, _, _)| (id, unit, job_type, state))
+        .collect();
+    let job_stats = tracker
+        .lock()
+        .await
+        .take_stats(&queue, listed_at, stuck_job_threshold);
+
+    let mut machine_stats = locked_machine_stats.write().await;
+    machine_stats.jobs = Some(job_stats);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(0.0, percentile(&[], 50.0));
+        let values: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(50.0, percentile(&values, 50.0));
+        assert_eq!(90.0, percentile(&values, 90.0));
+        assert_eq!(99.0, percentile(&values, 99.0));
+        assert_eq!(100.0, percentile(&values, 100.0));
+        assert_eq!(7.0, percentile(&[7.0], 99.0));
+    }
+
+    #[test]
+    fn test_job_tracker() {
+        let start = Instant::now();
+        let mut tracker = JobTracker::default();
+        tracker.record_job_new(1, start);
+        tracker.record_job_type(1, "start");
+        tracker.record_job_new(2, start);
+        tracker.record_job_type(2, "start");
+        tracker.record_job_new(3, start);
+        tracker.record_job_new(7, start);
+        tracker.record_job_removed(1, "done", start + Duration::from_millis(10));
+        tracker.record_job_removed(2, "failed", start + Duration::from_millis(30));
+        // Type lookup failed as the job was already gone
+        tracker.record_job_removed(3, "done", start + Duration::from_millis(5));
+        // Type lookup answered after JobRemoved
+        tracker.record_job_removed(7, "done", start + Duration::from_millis(20));
+        tracker.record_job_type(7, "stop");
+        // Started before we were tracking
+        tracker.record_job_removed(4, "timeout", start);
+
+        let queue = vec![
+            (
+                5,
+                String::from("slow.service"),
+                String::from("start"),
+                String::from("running"),
+            ),
+            (
+                6,
+                String::from("waiting.service"),
+                String::from("stop"),
+                String::from("waiting"),
+            ),
+        ];
+        let stats = tracker.take_stats(&queue, start, Duration::from_secs(60));
+        assert_eq!(
+            JobResultCounts {
+                done: 3,
+                failed: 1,
+                timeout: 1,
+                ..Default::default()
+            },
+            stats.results
+        );
+        assert_eq!(
+            JobTypeStats {
+                results: JobResultCounts {
+                    done: 1,
+                    failed: 1,
+                    ..Default::default()
+                },
+                duration_ms_p50: 10.0,
+                duration_ms_p90: 30.0,
+                duration_ms_p99: 30.0,
+                duration_ms_max: 30.0,
+            },
+            stats.by_type["start"]
+        );
+        assert_eq!(1, stats.by_type["stop"].results.done);
+        assert_eq!(20.0, stats.by_type["stop"].duration_ms_max);
+        assert_eq!(1, stats.by_type[UNKNOWN_JOB_TYPE].results.timeout);
+        assert_eq!(5.0, stats.by_type[UNKNOWN_JOB_TYPE].duration_ms_max);
+        assert_eq!(2, stats.queued);
+        assert!(stats.stuck_jobs.is_empty());
+
+        // Job 5 is still queued two minutes later, job 6 left without a signal
+        let later = start + Duration::from_secs(120);
+        let stats = tracker.take_stats(&queue[..1], later, Duration::from_secs(60));
+        assert_eq!(JobResultCounts::default(), stats.results);
+        assert!(stats.by_type.is_empty());
+        assert_eq!(
+            vec![StuckJob {
+                id: 5,
+                unit: String::from("slow.service"),
+                job_type: String::from("start"),
+                state: String::from("running"),
+                age_secs: 120,
+            }],
+            stats.stuck_jobs
+        );
+        assert!(!tracker.running.contains_key(&6));
+    }
+}
@@ -15,8 +15,40 @@ use crate::units;
 use crate::MachineStats;
 use crate::MonitordStats;
 
+/// Fields that only ever increase. Matched against struct field names and the
+/// last segment of flat keys, which differ for pid1's `cpu_time_user`.
+const COUNTER_FIELDS: &[&str] = &[
+    "cpu_time_kernel",
+    "cpu_time_user",
+    "cpu_user_kernel",
+    "cpuusage_nsec",
+    "ioread_bytes",
+    "ioread_operations",
+    "iowrite_bytes",
+    "iowrite_operations",
+    "ipegress_bytes",
+    "ipegress_packets",
+    "ipingress_bytes",
+    "ipingress_packets",
+    "nfailed_jobs",
+    "nrestarts",
+    "oom",
+    "oom_group_kill",
+    "oom_kill",
+    "total_usec",
+];
+
+/// Flat key suffix of PSI stall time totals (e.g. `memory_some_total_usec`)
+const PRESSURE_TOTAL_SUFFIX: &str = "_total_usec";
+
+/// Whether a struct field or flat key field segment is a counter, for the
+/// sinks that have a counter type (Prometheus, StatsD)
+pub(crate) fn is_counter_field(field: &str) -> bool {
+    COUNTER_FIELDS.contains(&field) || field.ends_with(PRESSURE_TOTAL_SUFFIX)
+}
+
 /// Add a prefix if the config specifies one
-fn gen_base_metric_key(key_prefix: &str, metric_name: &str) -> String {
+pub(crate) fn gen_base_metric_key(key_prefix: &str, metric_name: &str) -> String {
     match key_prefix.is_empty() {
         true => String::from(metric_name),
         false => format!("{}.{}", key_prefix, metric_name),
@@ -114,6 +146,113 @@ fn flatten_pid1(
     ]
 }
 
+fn flatten_manager(
+    optional_manager_stats: &Option<crate::manager::ManagerStats>,
+    key_prefix: &str,
+) -> Vec<(String, serde_json::Value)> {
+    let manager_stats = match optional_manager_stats {
+        Some(ms) => ms,
+        None => {
+            debug!("Skipping flattening manager stats as we got None ...");
+            return Vec::new();
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "manager");
+    let mut flat_stats = Vec::new();
+    if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(manager_stats) {
+        for (field_name, value) in map {
+            flat_stats.push((format!("{base_metric_name}.{field_name}"), value));
+        }
+    }
+    flat_stats
+}
+
+fn flatten_manager_defaults(
+    optional_manager_defaults_stats: &Option<crate::manager_defaults::ManagerDefaultsStats>,
+    key_prefix: &str,
+) -> Vec<(String, serde_json::Value)> {
+    let manager_defaults_stats = match optional_manager_defaults_stats {
+        Some(mds) => mds,
+        None => {
+            debug!("Skipping flattening manager defaults as we got None ...");
+            return Vec::new();
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "manager_defaults");
+    let mut flat_stats = vec![(
+        format!("{base_metric_name}.hash"),
+        manager_defaults_stats.hash.clone().into(),
+    )];
+    for (name, value) in &manager_defaults_stats.values {
+        let value = match value {
+            crate::manager_defaults::ManagerDefaultValue::Bool(b) => (*b as u64).into(),
+            crate::manager_defaults::ManagerDefaultValue::UInt(u) => (*u).into(),
+            crate::manager_defaults::ManagerDefaultValue::Int(i) => (*i).into(),
+            crate::manager_defaults::ManagerDefaultValue::String(s) => s.clone().into(),
+        };
+        flat_stats.push((format!("{base_metric_name}.{name}"), value));
+    }
+    flat_stats
+}
+
+fn flatten_tainted(
+    optional_tainted_stats: &Option<crate::tainted::TaintedStats>,
+    key_prefix: &str,
+) -> Vec<(String, serde_json::Value)> {
+    let tainted_stats = match optional_tainted_stats {
+        Some(ts) => ts,
+        None => {
+            debug!("Skipping flattening tainted stats as we got None ...");
+            return Vec::new();
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "tainted");
+    let mut flat_stats = Vec::new();
+    if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(tainted_stats) {
+        for (field_name, value) in map {
+            if let Some(b) = value.as_bool() {
+                flat_stats.push((
+                    format!("{base_metric_name}.{field_name}"),
+                    (b as u64).into(),
+                ));
+            }
+        }
+    }
+    flat_stats.push((
+        format!("{base_metric_name}.unknown"),
+        (tainted_stats.unknown.len() as u64).into(),
+    ));
+    flat_stats
+}
+
+fn flatten_watchdog(
+    optional_watchdog_stats: &Option<crate::watchdog::WatchdogStats>,
+    key_prefix: &str,
+) -> Vec<(String, serde_json::Value)> {
+    let watchdog_stats = match optional_watchdog_stats {
+        Some(ws) => ws,
+        None => {
+            debug!("Skipping flattening watchdog stats as we got None ...");
+            return Vec::new();
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "watchdog");
+    let mut flat_stats = Vec::new();
+    if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(watchdog_stats) {
+        for (field_name, value) in map {
+            // Skip the device path
+            if value.is_number() {
+                flat_stats.push((format!("{base_metric_name}.{field_name}"), value));
+            }
+        }
+    }
+    flat_stats
+}
+
 fn flatten_unit_files_scope(
     scope: &units::UnitFilesScope,
     base: &str,
@@ -147,6 +286,111 @@ fn flatten_unit_files(
     flat_stats
 }
 
+fn flatten_oom(
+    optional_oom_stats: &Option<crate::oom::OomStats>,
+    key_prefix: &str,
+) -> Vec<(String, serde_json::Value)> {
+    let oom_stats = match optional_oom_stats {
+        Some(os) => os,
+        None => {
+            debug!("Skipping flattening OOM stats as we got None ...");
+            return Vec::new();
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "oom");
+    let mut flat_stats = Vec::new();
+    let services = oom_stats
+        .services
+        .iter()
+        .map(|(service_name, stats)| (format!("services.{service_name}"), stats));
+    for (key, service_oom_stats) in
+        std::iter::once((String::from("total"), &oom_stats.total)).chain(services)
+    {
+        if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(service_oom_stats) {
+            for (field_name, value) in map {
+                flat_stats.push((format!("{base_metric_name}.{key}.{field_name}"), value));
+            }
+        }
+    }
+    if let Some(host_oom_kill) = oom_stats.host_oom_kill {
+        flat_stats.push((
+            format!("{base_metric_name}.host_oom_kill"),
+            host_oom_kill.into(),
+        ));
+    }
+    flat_stats
+}
+
+/// `<resource>_<kind>_<field>` keys of a cgroup's PSI, prefixed by `key_base`
+fn flatten_cgroup_pressure(
+    pressure: &crate::cgroup::CgroupPressure,
+    key_base: &str,
+) -> Vec<(String, serde_json::Value)> {
+    let mut flat_stats = Vec::new();
+    for (resource, kind, line) in pressure.lines() {
+        if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(line) {
+            for (field_name, value) in map {
+                flat_stats.push((format!("{key_base}{resource}_{kind}_{field_name}"), value));
+            }
+        }
+    }
+    flat_stats
+}
+
+fn flatten_pressure(
+    optional_pressure_stats: &Option<crate::pressure::PressureStats>,
+    key_prefix: &str,
+) -> Vec<(String, serde_json::Value)> {
+    let pressure_stats = match optional_pressure_stats {
+        Some(ps) => ps,
+        None => {
+            debug!("Skipping flattening pressure stats as we got None ...");
+            return Vec::new();
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "pressure");
+    let mut flat_stats = Vec::new();
+    for (slice, pressure) in &pressure_stats.slices {
+        flat_stats.extend(flatten_cgroup_pressure(
+            pressure,
+            &format!("{base_metric_name}.{slice}."),
+        ));
+    }
+    flat_stats
+}
+
+fn flatten_slices(
+    optional_slices_stats: &Option<crate::slices::SlicesStats>,
+    key_prefix: &str,
+) -> Vec<(String, serde_json::Value)> {
+    let slices_stats = match optional_slices_stats {
+        Some(ss) => ss,
+        None => {
+            debug!("Skipping flattening slice stats as we got None ...");
+            return Vec::new();
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "slices");
+    let mut flat_stats = Vec::new();
+    for (slice, slice_stats) in &slices_stats.slices {
+        if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(&slice_stats.usage) {
+            for (field_name, value) in map {
+                if value.is_number() {
+                    flat_stats.push((format!("{base_metric_name}.{slice}.{field_name}"), value));
+                }
+            }
+        }
+        flat_stats.extend(flatten_cgroup_pressure(
+            &slice_stats.pressure,
+            &format!("{base_metric_name}.{slice}.pressure_"),
+        ));
+    }
+    flat_stats
+}
+
 fn flatten_services(
     service_stats_hash: &HashMap<String, units::ServiceStats>,
     key_prefix: &str,
@@ -163,6 +407,12 @@ fn flatten_services(
                 }
             }
         }
+        if let Some(pressure) = &service_stats.pressure {
+            flat_stats.extend(flatten_cgroup_pressure(
+                pressure,
+                &format!("{base_metric_name}.{service_name}.pressure_"),
+            ));
+        }
     }
     flat_stats
 }
@@ -212,11 +462,29 @@ fn flatten_unit_states(
     flat_stats
 }
 
+fn flatten_unit_state_transitions(
+    transitions_hash: &HashMap<String, units::UnitStateTransitions>,
+    key_prefix: &str,
+) -> Vec<(String, serde_json::Value)> {
+    let mut flat_stats = Vec::new();
+    let base_metric_name = gen_base_metric_key(key_prefix, "unit_state_transitions");
+
+    for (unit_name, transitions) in transitions_hash.iter() {
+        if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(transitions) {
+            for (field_name, value) in map {
+                let key = format!("{base_metric_name}.{unit_name}.{field_name}");
+                flat_stats.push((key, value));
+            }
+        }
+    }
+    flat_stats
+}
+
 /// Lightweight view of `SystemdUnitStats` containing only the numeric counters.
 /// Used by `flatten_units` to avoid serializing the nested `service_stats`,
 /// `timer_stats`, and `unit_states` hashmaps, keeping flattening O(number_of_counters).
 #[derive(serde::Serialize)]
-struct UnitCounters {
+pub(crate) struct UnitCounters {
     activating_units: u64,
     active_units: u64,
     automount_units: u64,
@@ -314,6 +582,14 @@ fn flatten_machines(
             &machine_key_prefix,
         ));
         flat_stats.extend(flatten_pid1(&stats.pid1, &machine_key_prefix));
+        flat_stats.extend(flatten_manager(&stats.manager, &machine_key_prefix));
+        flat_stats.extend(flatten_manager_defaults(
+            &stats.manager_defaults,
+            &machine_key_prefix,
+        ));
+        flat_stats.extend(flatten_tainted(&stats.tainted, &machine_key_prefix));
+        flat_stats.extend(flatten_pressure(&stats.pressure, &machine_key_prefix));
+        flat_stats.extend(flatten_slices(&stats.slices, &machine_key_prefix));
         flat_stats.insert(
             gen_base_metric_key(&machine_key_prefix, "system-state"),
             (stats.system_state as u64).into(),
@@ -484,6 +760,119 @@ fn flatten_boot_blame(
     flat_stats
 }
 
+fn flatten_boot_timeline(
+    optional_boot_timeline: &Option<crate::boot_timeline::BootTimelineStats>,
+    key_prefix: &str,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut flat_stats: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+    let boot_timeline = match optional_boot_timeline {
+        Some(bt) => bt,
+        None => {
+            debug!("Skipping flattening boot timeline stats as we got None ...");
+            return flat_stats;
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "boot.timeline");
+    if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(boot_timeline) {
+        for (field_name, value) in map {
+            let key = format!("{base_metric_name}.{field_name}");
+            if value.is_number() {
+                flat_stats.insert(key, value);
+            } else if let Some(b) = value.as_bool() {
+                flat_stats.insert(key, (b as u64).into());
+            }
+        }
+    }
+
+    flat_stats
+}
+
+fn flatten_boot_critical_chain(
+    optional_boot_critical_chain: &Option<crate::boot_critical_chain::BootCriticalChainStats>,
+    key_prefix: &str,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut flat_stats: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+    let boot_critical_chain = match optional_boot_critical_chain {
+        Some(bcc) => bcc,
+        None => {
+            debug!("Skipping flattening boot critical chain as we got None ...");
+            return flat_stats;
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "boot.critical_chain");
+    flat_stats.insert(
+        format!("{base_metric_name}.length"),
+        (boot_critical_chain.chain.len() as u64).into(),
+    );
+    for hop in &boot_critical_chain.chain {
+        flat_stats.insert(
+            format!("{base_metric_name}.{}.activated_secs", hop.unit),
+            hop.activated_secs.into(),
+        );
+        flat_stats.insert(
+            format!("{base_metric_name}.{}.activation_secs", hop.unit),
+            hop.activation_secs.into(),
+        );
+    }
+
+    flat_stats
+}
+
+fn flatten_boot_history_comparison(
+    comparison: &crate::boot_history::BootHistoryComparison,
+    base: &str,
+) -> Vec<(String, serde_json::Value)> {
+    vec![
+        (format!("{base}.secs"), comparison.secs.into()),
+        (format!("{base}.median_secs"), comparison.median_secs.into()),
+        (format!("{base}.delta_secs"), comparison.delta_secs.into()),
+        (
+            format!("{base}.regressed"),
+            (comparison.regressed as u64).into(),
+        ),
+    ]
+}
+
+fn flatten_boot_history(
+    optional_boot_history: &Option<crate::boot_history::BootHistoryStats>,
+    key_prefix: &str,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut flat_stats: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+    let boot_history = match optional_boot_history {
+        Some(bh) => bh,
+        None => {
+            debug!("Skipping flattening boot history as we got None ...");
+            return flat_stats;
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "boot.history");
+    flat_stats.insert(
+        format!("{base_metric_name}.previous_boots"),
+        boot_history.previous_boots.into(),
+    );
+    flat_stats.insert(
+        format!("{base_metric_name}.regressed_units"),
+        boot_history.regressed_units.into(),
+    );
+    if let Some(total) = &boot_history.total {
+        flat_stats.extend(flatten_boot_history_comparison(
+            total,
+            &format!("{base_metric_name}.total"),
+        ));
+    }
+    for (unit_name, comparison) in &boot_history.units {
+        flat_stats.extend(flatten_boot_history_comparison(
+            comparison,
+            &format!("{base_metric_name}.units.{unit_name}"),
+        ));
+    }
+
+    flat_stats
+}
+
 fn flatten_verify_stats(
     optional_verify_stats: &Option<crate::verify::VerifyStats>,
     key_prefix: &str,
@@ -513,6 +902,68 @@ fn flatten_verify_stats(
     flat_stats
 }
 
+fn flatten_jobs(
+    optional_job_stats: &Option<crate::jobs::JobStats>,
+    key_prefix: &str,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut flat_stats: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+    let job_stats = match optional_job_stats {
+        Some(js) => js,
+        None => {
+            debug!("Skipping flattening job stats as we got None ...");
+            return flat_stats;
+        }
+    };
+
+    let base_metric_name = gen_base_metric_key(key_prefix, "jobs");
+    flat_stats.insert(
+        format!("{base_metric_name}.queued"),
+        job_stats.queued.into(),
+    );
+    flat_stats.insert(
+        format!("{base_metric_name}.stuck"),
+        (job_stats.stuck_jobs.len() as u64).into(),
+    );
+    if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(&job_stats.results) {
+        for (result, count) in map {
+            flat_stats.insert(format!("{base_metric_name}.results.{result}"), count);
+        }
+    }
+    for (job_type, type_stats) in &job_stats.by_type {
+        let type_key = format!("{base_metric_name}.types.{job_type}");
+        if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(&type_stats.results) {
+            for (result, count) in map {
+                flat_stats.insert(format!("{type_key}.{result}"), count);
+            }
+        }
+        flat_stats.insert(
+            format!("{type_key}.duration_ms_p50"),
+            type_stats.duration_ms_p50.into(),
+        );
+        flat_stats.insert(
+            format!("{type_key}.duration_ms_p90"),
+            type_stats.duration_ms_p90.into(),
+        );
+        flat_stats.insert(
+            format!("{type_key}.duration_ms_p99"),
+            type_stats.duration_ms_p99.into(),
+        );
+        flat_stats.insert(
+            format!("{type_key}.duration_ms_max"),
+            type_stats.duration_ms_max.into(),
+        );
+    }
+    // systemd merges jobs so a unit has at most one queued job
+    for stuck_job in &job_stats.stuck_jobs {
+        flat_stats.insert(
+            format!("{base_metric_name}.stuck_jobs.{}.age_secs", stuck_job.unit),
+            stuck_job.age_secs.into(),
+        );
+    }
+
+    flat_stats
+}
+
 fn flatten_collector_timings(
     timings: &[crate::CollectorTiming],
     key_prefix: &str,
@@ -562,6 +1013,18 @@ fn flatten_units_collection_timings(
         format!("{base_metric_name}.service_dbus_fetches"),
         timings.service_dbus_fetches.into(),
     );
+    flat_stats.insert(
+        format!("{base_metric_name}.service_cgroup_reads"),
+        timings.service_cgroup_reads.into(),
+    );
+    flat_stats.insert(
+        format!("{base_metric_name}.properties_get_all_calls"),
+        timings.properties_get_all_calls.into(),
+    );
+    flat_stats.insert(
+        format!("{base_metric_name}.properties_get_all_ms"),
+        timings.properties_get_all_ms.into(),
+    );
     // Rank is encoded in the key (not just the unit name) so ordering survives
     // flattening; the leaf value stays a plain number like every other key here.
     for (idx, (unit_name, duration_ms)) in timings.slowest_units.iter().enumerate() {
@@ -573,8 +1036,52 @@ fn flatten_units_collection_timings(
     flat_stats
 }
 
+/// Flatten a custom collector's JSON value. Objects and arrays become key
+/// segments, bools become 1/0 and nulls are skipped.
+pub(crate) fn flatten_extra_value(
+    key: String,
+    value: &serde_json::Value,
+    flat_stats: &mut BTreeMap<String, serde_json::Value>,
+) {
+    match value {
+        serde_json::Value::Null => (),
+        serde_json::Value::Bool(b) => {
+            flat_stats.insert(key, (if *b { 1u64 } else { 0u64 }).into());
+        }
+        serde_json::Value::Number(_) | serde_json::Value::String(_) => {
+            flat_stats.insert(key, value.clone());
+        }
+        serde_json::Value::Array(values) => {
+            for (idx, v) in values.iter().enumerate() {
+                flatten_extra_value(format!("{key}.{idx}"), v, flat_stats);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                flatten_extra_value(format!("{key}.{k}"), v, flat_stats);
+            }
+        }
+    }
+}
+
+fn flatten_extra(
+    extra: &BTreeMap<String, serde_json::Value>,
+    key_prefix: &str,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut flat_stats: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+    let base_metric_name = gen_base_metric_key(key_prefix, "extra");
+    for (collector_name, value) in extra {
+        flatten_extra_value(
+            format!("{base_metric_name}.{collector_name}"),
+            value,
+            &mut flat_stats,
+        );
+    }
+    flat_stats
+}
+
 /// Take the standard returned structs and move all to a flat BTreeMap<str, float|int> like JSON
-fn flatten_stats(
+pub(crate) fn flatten_stats(
     stats_struct: &MonitordStats,
     key_prefix: &str,
 ) -> BTreeMap<String, serde_json::Value> {
@@ -593,6 +1100,16 @@ fn flatten_stats(
     ));
     flat_stats.extend(flatten_networkd(&stats_struct.networkd, key_prefix));
     flat_stats.extend(flatten_pid1(&stats_struct.pid1, key_prefix));
+    flat_stats.extend(flatten_manager(&stats_struct.manager, key_prefix));
+    flat_stats.extend(flatten_manager_defaults(
+        &stats_struct.manager_defaults,
+        key_prefix,
+    ));
+    flat_stats.extend(flatten_tainted(&stats_struct.tainted, key_prefix));
+    flat_stats.extend(flatten_watchdog(&stats_struct.watchdog, key_prefix));
+    flat_stats.extend(flatten_oom(&stats_struct.oom, key_prefix));
+    flat_stats.extend(flatten_pressure(&stats_struct.pressure, key_prefix));
+    flat_stats.extend(flatten_slices(&stats_struct.slices, key_prefix));
     flat_stats.insert(
         gen_base_metric_key(key_prefix, "system-state"),
         (stats_struct.system_state as u64).into(),
@@ -606,6 +1123,10 @@ fn flatten_stats(
         &stats_struct.units.unit_states,
         key_prefix,
     ));
+    flat_stats.extend(flatten_unit_state_transitions(
+        &stats_struct.units.unit_state_transitions,
+        key_prefix,
+    ));
     flat_stats.extend(flatten_units(&stats_struct.units, key_prefix));
     flat_stats.extend(flatten_unit_files(
         &stats_struct.units.unit_files,
@@ -618,7 +1139,18 @@ fn flatten_stats(
     flat_stats.extend(flatten_machines(&stats_struct.machines, key_prefix));
     flat_stats.extend(flatten_dbus_stats(&stats_struct.dbus_stats, key_prefix));
     flat_stats.extend(flatten_boot_blame(&stats_struct.boot_blame, key_prefix));
+    flat_stats.extend(flatten_boot_timeline(
+        &stats_struct.boot_timeline,
+        key_prefix,
+    ));
+    flat_stats.extend(flatten_boot_critical_chain(
+        &stats_struct.boot_critical_chain,
+        key_prefix,
+    ));
+    flat_stats.extend(flatten_boot_history(&stats_struct.boot_history, key_prefix));
     flat_stats.extend(flatten_verify_stats(&stats_struct.verify_stats, key_prefix));
+    flat_stats.extend(flatten_jobs(&stats_struct.jobs, key_prefix));
+    flat_stats.extend(flatten_extra(&stats_struct.extra, key_prefix));
     flat_stats
 }
 
@@ -643,6 +1175,9 @@ mod tests {
   "boot.blame.sys-module-fuse.device": 16.21,
   "collection_timings.list_units_ms": 5.0,
   "collection_timings.per_unit_loop_ms": 37.0,
+  "collection_timings.properties_get_all_calls": 6,
+  "collection_timings.properties_get_all_ms": 9.5,
+  "collection_timings.service_cgroup_reads": 0,
   "collection_timings.service_dbus_fetches": 1,
   "collection_timings.slowest_units.0.unittest.service": 12.5,
   "collection_timings.slowest_units.1.unittest.timer": 8.25,
@@ -656,6 +1191,9 @@ mod tests {
   "collector_timings.units.success": 1,
   "machines.foo.collection_timings.list_units_ms": 0.0,
   "machines.foo.collection_timings.per_unit_loop_ms": 0.0,
+  "machines.foo.collection_timings.properties_get_all_calls": 0,
+  "machines.foo.collection_timings.properties_get_all_ms": 0.0,
+  "machines.foo.collection_timings.service_cgroup_reads": 0,
   "machines.foo.collection_timings.service_dbus_fetches": 0,
   "machines.foo.collection_timings.state_dbus_fetches": 0,
   "machines.foo.collection_timings.timer_dbus_fetches": 0,
@@ -709,6 +1247,9 @@ mod tests {
   "services.unittest.service.active_enter_timestamp": 0,
   "services.unittest.service.active_exit_timestamp": 0,
   "services.unittest.service.cpuusage_nsec": 0,
+  "services.unittest.service.exec_main_code": 0,
+  "services.unittest.service.exec_main_pid": 0,
+  "services.unittest.service.exec_main_status": 0,
   "services.unittest.service.inactive_exit_timestamp": 0,
   "services.unittest.service.ioread_bytes": 0,
   "services.unittest.service.ioread_operations": 0,
@@ -717,6 +1258,7 @@ mod tests {
   "services.unittest.service.nrestarts": 0,
   "services.unittest.service.processes": 0,
   "services.unittest.service.restart_usec": 0,
+  "services.unittest.service.result": 0,
   "services.unittest.service.state_change_timestamp": 0,
   "services.unittest.service.status_errno": -69,
   "services.unittest.service.tasks_current": 0,
@@ -819,6 +1361,18 @@ mod tests {
                     success: false,
                 },
             ],
+            boot_timeline: None,
+            boot_critical_chain: None,
+            boot_history: None,
+            manager: None,
+            manager_defaults: None,
+            tainted: None,
+            watchdog: None,
+            oom: None,
+            pressure: None,
+            slices: None,
+            jobs: None,
+            extra: BTreeMap::new(),
         };
         stats.units.collection_timings = units::UnitsCollectionTimings {
             list_units_ms: 5.0,
@@ -827,6 +1381,9 @@ mod tests {
             timer_dbus_fetches: 4,
             state_dbus_fetches: 0,
             service_dbus_fetches: 1,
+            service_cgroup_reads: 0,
+            properties_get_all_calls: 6,
+            properties_get_all_ms: 9.5,
             slowest_units: vec![
                 ("unittest.service".to_string(), 12.5),
                 ("unittest.timer".to_string(), 8.25),
@@ -899,7 +1456,7 @@ mod tests {
     #[test]
     fn test_flatten_map() {
         let json_flat_map = flatten_stats(&return_monitord_stats(), "");
-        assert_eq!(129, json_flat_map.len());
+        assert_eq!(139, json_flat_map.len());
     }
 
     #[test]
@@ -919,6 +1476,286 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_flatten_extra() {
+        let extra = BTreeMap::from([(
+            String::from("custom"),
+            serde_json::json!({"count": 2, "ok": true, "missing": null, "list": [1.5]}),
+        )]);
+        assert_eq!(
+            BTreeMap::from([
+                (
+                    String::from("monitord.extra.custom.count"),
+                    serde_json::Value::from(2)
+                ),
+                (
+                    String::from("monitord.extra.custom.list.0"),
+                    serde_json::Value::from(1.5)
+                ),
+                (
+                    String::from("monitord.extra.custom.ok"),
+                    serde_json::Value::from(1)
+                ),
+            ]),
+            flatten_extra(&extra, "monitord")
+        );
+    }
+
+    #[test]
+    fn test_flatten_manager() {
+        let manager_stats = crate::manager::ManagerStats {
+            nfailed_jobs: 3,
+            progress: 1.0,
+            ..Default::default()
+        };
+        let flat_stats: BTreeMap<String, serde_json::Value> =
+            flatten_manager(&Some(manager_stats), "monitord")
+                .into_iter()
+                .collect();
+        assert_eq!(6, flat_stats.len());
+        assert_eq!(
+            Some(&serde_json::Value::from(3)),
+            flat_stats.get("monitord.manager.nfailed_jobs")
+        );
+        assert_eq!(
+            Some(&serde_json::Value::from(1.0)),
+            flat_stats.get("monitord.manager.progress")
+        );
+        assert!(flatten_manager(&None, "monitord").is_empty());
+    }
+
+    #[test]
+    fn test_flatten_manager_defaults() {
+        let manager_defaults_stats = crate::manager_defaults::ManagerDefaultsStats {
+            hash: String::from("0123456789abcdef"),
+            values: BTreeMap::from([
+                (
+                    String::from("limit_nofile"),
+                    crate::manager_defaults::ManagerDefaultValue::UInt(524288),
+                ),
+                (
+                    String::from("memory_accounting"),
+                    crate::manager_defaults::ManagerDefaultValue::Bool(true),
+                ),
+                (
+                    String::from("oompolicy"),
+                    crate::manager_defaults::ManagerDefaultValue::String(String::from("stop")),
+                ),
+            ]),
+        };
+        let flat_stats: BTreeMap<String, serde_json::Value> =
+            flatten_manager_defaults(&Some(manager_defaults_stats), "monitord")
+                .into_iter()
+                .collect();
+        assert_eq!(
+            BTreeMap::from([
+                (
+                    String::from("monitord.manager_defaults.hash"),
+                    serde_json::Value::from("0123456789abcdef")
+                ),
+                (
+                    String::from("monitord.manager_defaults.limit_nofile"),
+                    serde_json::Value::from(524288)
+                ),
+                (
+                    String::from("monitord.manager_defaults.memory_accounting"),
+                    serde_json::Value::from(1)
+                ),
+                (
+                    String::from("monitord.manager_defaults.oompolicy"),
+                    serde_json::Value::from("stop")
+                ),
+            ]),
+            flat_stats
+        );
+        assert!(flatten_manager_defaults(&None, "monitord").is_empty());
+    }
+
+    #[test]
+    fn test_flatten_oom() {
+        let service_oom_stats = crate::oom::ServiceOomStats {
+            oom_kill: 2,
+            oom_kill_delta: 1,
+            ..Default::default()
+        };
+        let oom_stats = crate::oom::OomStats {
+            services: HashMap::from([(String::from("foo.service"), service_oom_stats.clone())]),
+            total: service_oom_stats,
+            host_oom_kill: Some(5),
+        };
+        let flat_stats: BTreeMap<String, serde_json::Value> =
+            flatten_oom(&Some(oom_stats), "monitord")
+                .into_iter()
+                .collect();
+        assert_eq!(17, flat_stats.len());
+        assert_eq!(
+            Some(&serde_json::Value::from(2)),
+            flat_stats.get("monitord.oom.services.foo.service.oom_kill")
+        );
+        assert_eq!(
+            Some(&serde_json::Value::from(1)),
+            flat_stats.get("monitord.oom.total.oom_kill_delta")
+        );
+        assert_eq!(
+            Some(&serde_json::Value::from(5)),
+            flat_stats.get("monitord.oom.host_oom_kill")
+        );
+        assert!(flatten_oom(&None, "monitord").is_empty());
+    }
+
+    #[test]
+    fn test_flatten_slices() {
+        let slice_stats = crate::slices::SliceStats {
+            usage: crate::cgroup::CgroupStats {
+                cpuusage_nsec: Some(1_000),
+                tasks_current: Some(12),
+                ..Default::default()
+            },
+            pressure: crate::cgroup::CgroupPressure {
+                io: Some(crate::cgroup::ResourcePressure::default()),
+                ..Default::default()
+            },
+        };
+        let slices_stats = crate::slices::SlicesStats {
+            slices: HashMap::from([(String::from("user-1000.slice"), slice_stats)]),
+        };
+        let flat_stats: HashMap<String, serde_json::Value> =
+            flatten_slices(&Some(slices_stats), "monitord")
+                .into_iter()
+                .collect();
+        // 2 usage values read + io some/full PSI lines
+        assert_eq!(10, flat_stats.len());
+        assert_eq!(
+            Some(&serde_json::json!(12)),
+            flat_stats.get("monitord.slices.user-1000.slice.tasks_current")
+        );
+        assert!(flat_stats.contains_key("monitord.slices.user-1000.slice.pressure_io_full_avg300"));
+        assert!(flatten_slices(&None, "monitord").is_empty());
+    }
+
+    #[test]
+    fn test_flatten_pressure() {
+        let pressure = crate::cgroup::CgroupPressure {
+            memory: Some(crate::cgroup::ResourcePressure {
+                full: crate::cgroup::PressureLine {
+                    avg10: 1.5,
+                    total_usec: 42,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let pressure_stats = crate::pressure::PressureStats {
+            slices: HashMap::from([(String::from("system.slice"), pressure.clone())]),
+        };
+        let flat_stats: HashMap<String, serde_json::Value> =
+            flatten_pressure(&Some(pressure_stats), "monitord")
+                .into_iter()
+                .collect();
+        // some and full lines of memory only
+        assert_eq!(8, flat_stats.len());
+        assert_eq!(
+            Some(&serde_json::json!(1.5)),
+            flat_stats.get("monitord.pressure.system.slice.memory_full_avg10")
+        );
+        assert!(flatten_pressure(&None, "monitord").is_empty());
+
+        let service_stats = HashMap::from([(
+            String::from("foo.service"),
+            units::ServiceStats {
+                pressure: Some(pressure),
+                ..Default::default()
+            },
+        )]);
+        let flat_stats: HashMap<String, serde_json::Value> =
+            flatten_services(&service_stats, "monitord")
+                .into_iter()
+                .collect();
+        assert_eq!(
+            Some(&serde_json::json!(42)),
+            flat_stats.get("monitord.services.foo.service.pressure_memory_full_total_usec")
+        );
+    }
+
+    #[test]
+    fn test_flatten_tainted() {
+        let tainted_stats = crate::tainted::TaintedStats::from("cgroupsv1:new-taint");
+        let flat_stats: BTreeMap<String, serde_json::Value> =
+            flatten_tainted(&Some(tainted_stats), "monitord")
+                .into_iter()
+                .collect();
+        assert_eq!(
+            Some(&serde_json::Value::from(1)),
+            flat_stats.get("monitord.tainted.cgroupsv1")
+        );
+        assert_eq!(
+            Some(&serde_json::Value::from(0)),
+            flat_stats.get("monitord.tainted.unmerged_usr")
+        );
+        assert_eq!(
+            Some(&serde_json::Value::from(1)),
+            flat_stats.get("monitord.tainted.unknown")
+        );
+    }
+
+    #[test]
+    fn test_flatten_jobs() {
+        let job_stats = crate::jobs::JobStats {
+            results: crate::jobs::JobResultCounts {
+                done: 3,
+                ..Default::default()
+            },
+            by_type: HashMap::from([(
+                String::from("start"),
+                crate::jobs::JobTypeStats {
+                    duration_ms_p99: 12.5,
+                    ..Default::default()
+                },
+            )]),
+            queued: 1,
+            stuck_jobs: vec![crate::jobs::StuckJob {
+                id: 69,
+                unit: String::from("slow.service"),
+                job_type: String::from("start"),
+                state: String::from("running"),
+                age_secs: 600,
+            }],
+        };
+        let flat_stats = flatten_jobs(&Some(job_stats), "monitord");
+        assert_eq!(
+            Some(&serde_json::Value::from(1)),
+            flat_stats.get("monitord.jobs.queued")
+        );
+        assert_eq!(
+            Some(&serde_json::Value::from(1)),
+            flat_stats.get("monitord.jobs.stuck")
+        );
+        assert_eq!(
+            Some(&serde_json::Value::from(3)),
+            flat_stats.get("monitord.jobs.results.done")
+        );
+        assert_eq!(
+            Some(&serde_json::Value::from(12.5)),
+            flat_stats.get("monitord.jobs.types.start.duration_ms_p99")
+        );
+        assert_eq!(
+            Some(&serde_json::Value::from(600)),
+            flat_stats.get("monitord.jobs.stuck_jobs.slow.service.age_secs")
+        );
+        assert!(flatten_jobs(&None, "monitord").is_empty());
+    }
+
+    #[test]
+    fn test_is_counter_field() {
+        assert!(is_counter_field("nrestarts"));
+        assert!(is_counter_field("cpu_time_user"));
+        assert!(is_counter_field("cpu_user_kernel"));
+        assert!(is_counter_field("total_usec"));
+        assert!(is_counter_field("pressure_memory_full_total_usec"));
+        assert!(!is_counter_field("memory_current"));
+    }
+
     /// Ensure `UnitCounters` covers every scalar (non-hashmap) field of `SystemdUnitStats`.
     ///
     /// If a new counter field is added to `SystemdUnitStats` but not to `UnitCounters`
@@ -932,6 +1769,7 @@ mod tests {
             "service_stats",
             "timer_stats",
             "unit_states",
+            "unit_state_transitions",
             "collection_timings",
         ];
 
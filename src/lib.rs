@@ -4,6 +4,7 @@
 
 use std::sync::Arc;
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::time::Duration;
 use std::time::Instant;
@@ -20,25 +21,54 @@ use tracing::Instrument;
 pub enum MonitordError {
     #[error("D-Bus connection error: {0}")]
     ZbusError(#[from] zbus::Error),
+    #[error("Prometheus listener error: {0}")]
+    PrometheusError(#[from] prometheus::MonitordPrometheusError),
+    #[error("StatsD error: {0}")]
+    StatsdError(#[from] statsd::MonitordStatsdError),
+    #[error("Varlink server error: {0}")]
+    VarlinkServerError(#[from] varlink_server::MonitordVarlinkServerError),
 }
 
 pub mod boot;
+pub mod boot_critical_chain;
+pub mod boot_history;
+pub mod boot_timeline;
+pub mod cgroup;
+pub mod collector;
 pub mod config;
 pub(crate) mod dbus;
+pub mod dbus_service;
 pub mod dbus_stats;
+pub mod graphite;
+pub mod influx;
+pub mod jobs;
 pub mod json;
 pub mod logging;
 pub mod machines;
+pub mod manager;
+pub mod manager_defaults;
+pub(crate) mod metrics;
 pub mod networkd;
+pub mod oom;
 pub mod pid1;
+pub mod pressure;
+pub mod prometheus;
+pub mod sink;
+pub mod slices;
+pub mod statsd;
 pub mod system;
+pub mod tainted;
+pub mod textfile;
 pub mod timer;
 pub mod unit_constants;
+pub mod unit_events;
 pub mod units;
 pub mod varlink;
 pub mod varlink_networkd;
+pub mod varlink_server;
 pub mod varlink_units;
 pub mod verify;
+pub mod watchdog;
 
 pub const DEFAULT_DBUS_ADDRESS: &str = "unix:path=/run/dbus/system_bus_socket";
 
@@ -72,6 +102,27 @@ pub struct MachineStats {
     pub pid1: Option<pid1::Pid1Stats>,
     /// Overall systemd system state (e.g. running, degraded) inside the container
     pub system_state: system::SystemdSystemState,
+    /// systemd manager counters (failed jobs since boot, queued jobs, boot progress ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manager: Option<manager::ManagerStats>,
+    /// systemd manager Default* settings from system.conf and their hash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manager_defaults: Option<manager_defaults::ManagerDefaultsStats>,
+    /// Parsed systemd Tainted flags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tainted: Option<tainted::TaintedStats>,
+    /// PID 1 hardware watchdog settings and last ping
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watchdog: Option<watchdog::WatchdogStats>,
+    /// OOM killer counts from the cgroups of tracked services
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oom: Option<oom::OomStats>,
+    /// Pressure stall information of the top level slices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<pressure::PressureStats>,
+    /// Cgroup usage and PSI of the loaded slices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slices: Option<slices::SlicesStats>,
     /// Aggregated systemd unit counts and per-service/timer stats inside the container
     pub units: units::SystemdUnitStats,
     /// systemd version running inside the container
@@ -81,12 +132,24 @@ pub struct MachineStats {
     /// Boot blame statistics: slowest units at boot with activation times in seconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub boot_blame: Option<boot::BootBlameStats>,
+    /// Boot phase durations in seconds, like systemd-analyze time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boot_timeline: Option<boot_timeline::BootTimelineStats>,
+    /// Units that gated reaching the configured target at boot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boot_critical_chain: Option<boot_critical_chain::BootCriticalChainStats>,
+    /// This boot compared to the median of previous boots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boot_history: Option<boot_history::BootHistoryStats>,
     /// Unit verification error statistics
     pub verify_stats: Option<verify::VerifyStats>,
+    /// systemd job results, durations and stuck jobs since the previous run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<jobs::JobStats>,
 }
 
 /// Root struct containing all enabled monitord metrics for the host system and containers
-#[derive(serde::Serialize, serde::Deserialize, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct MonitordStats {
     /// systemd-networkd interface states and managed interface count
     pub networkd: networkd::NetworkdState,
@@ -94,6 +157,27 @@ pub struct MonitordStats {
     pub pid1: Option<pid1::Pid1Stats>,
     /// Overall systemd manager state (e.g. running, degraded, initializing)
     pub system_state: system::SystemdSystemState,
+    /// systemd manager counters (failed jobs since boot, queued jobs, boot progress ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manager: Option<manager::ManagerStats>,
+    /// systemd manager Default* settings from system.conf and their hash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manager_defaults: Option<manager_defaults::ManagerDefaultsStats>,
+    /// Parsed systemd Tainted flags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tainted: Option<tainted::TaintedStats>,
+    /// PID 1 hardware watchdog settings and last ping
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watchdog: Option<watchdog::WatchdogStats>,
+    /// OOM killer counts from the cgroups of tracked services
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oom: Option<oom::OomStats>,
+    /// Pressure stall information of the top level slices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<pressure::PressureStats>,
+    /// Cgroup usage and PSI of the loaded slices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slices: Option<slices::SlicesStats>,
     /// Aggregated systemd unit counts by type/state and per-service/timer detailed metrics
     pub units: units::SystemdUnitStats,
     /// Installed systemd version (major.minor.revision.os)
@@ -105,8 +189,20 @@ pub struct MonitordStats {
     /// Boot blame statistics: slowest units at boot with activation times in seconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub boot_blame: Option<boot::BootBlameStats>,
+    /// Boot phase durations in seconds, like systemd-analyze time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boot_timeline: Option<boot_timeline::BootTimelineStats>,
+    /// Units that gated reaching the configured target at boot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boot_critical_chain: Option<boot_critical_chain::BootCriticalChainStats>,
+    /// This boot compared to the median of previous boots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boot_history: Option<boot_history::BootHistoryStats>,
     /// Unit verification error statistics
     pub verify_stats: Option<verify::VerifyStats>,
+    /// systemd job results, durations and stuck jobs since the previous run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<jobs::JobStats>,
     /// End-to-end duration of the last stat collection run in milliseconds.
     pub stat_collection_run_time_ms: f64,
     /// Per-collector timings from the last run, sorted slowest first. Empty
@@ -114,6 +210,9 @@ pub struct MonitordStats {
     /// (sum of `elapsed_ms` / `stat_collection_run_time_ms`) and identify the
     /// gating collector (first entry) directly from this vector.
     pub collector_timings: Vec<CollectorTiming>,
+    /// Output of custom `collector::Collector`s keyed by collector name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, serde_json::Value>,
 }
 
 /// Print statistics in the format set in configuration
@@ -135,6 +234,9 @@ pub fn print_stats(
             "{}",
             serde_json::to_string_pretty(&stats).expect("Invalid JSON serialization")
         ),
+        config::MonitordOutputFormat::InfluxLine => {
+            print!("{}", influx::to_line_protocol(stats, key_prefix))
+        }
     }
 }
 
@@ -158,17 +260,18 @@ type TimedCollectorOutput = (String, anyhow::Result<()>, Duration, Duration);
 /// as an unrelated root trace instead of a child of the current collection run.
 fn spawn_timed<F>(
     join_set: &mut tokio::task::JoinSet<TimedCollectorOutput>,
-    name: &'static str,
+    name: impl Into<String>,
     collect_start: Instant,
     fut: F,
 ) where
     F: std::future::Future<Output = anyhow::Result<()>> + Send + 'static,
 {
+    let name: String = name.into();
     let parent_span = tracing::Span::current();
     let span = tracing::debug_span!(
         parent: &parent_span,
         "collector",
-        name,
+        name = name.as_str(),
         elapsed_ms = tracing::field::Empty,
         success = tracing::field::Empty,
     );
@@ -181,7 +284,7 @@ fn spawn_timed<F>(
             let elapsed = task_first_poll.elapsed();
             recording_span.record("elapsed_ms", elapsed.as_secs_f64() * 1000.0);
             recording_span.record("success", result.is_ok());
-            (name.to_string(), result, start_offset, elapsed)
+            (name, result, start_offset, elapsed)
         }
         .instrument(span),
     );
@@ -201,17 +304,34 @@ async fn get_or_create_dbus_connection(
     }
 }
 
-/// Main statistic collection function running what's required by configuration in parallel
-/// Takes an optional locked stats struct to update and to output stats to STDOUT or not.
-/// Takes an optional D-Bus connection. Returns `Some(connection)` if the
-/// collection cycle completed without errors (meaning the connection is reusable),
-/// `None` if errors occurred.
+/// Optional inputs to `stat_collector`. Defaults to a new stats struct and
+/// system bus connection with no custom collectors or sinks.
+#[derive(Default)]
+pub struct StatCollectorOptions {
+    /// Stats struct to update, e.g. to read it from another task
+    pub locked_stats: Option<Arc<RwLock<MonitordStats>>>,
+    /// D-Bus connection to reuse instead of connecting to the system bus
+    pub connection: Option<zbus::Connection>,
+    /// Custom collectors run every run. Their output is stored in `MonitordStats::extra`
+    pub collectors: collector::CollectorRegistry,
+    /// Sinks emitted to every run after the sinks enabled in config
+    pub sinks: Vec<Box<dyn sink::Sink>>,
+}
+
+/// Main statistic collection function running what's required by configuration in parallel.
+/// Stats are printed to STDOUT when `[stdout]` is enabled in config.
+/// Returns `Some(connection)` if the collection cycle completed without errors
+/// (meaning the connection is reusable), `None` if errors occurred.
 pub async fn stat_collector(
     config: config::Config,
-    maybe_locked_stats: Option<Arc<RwLock<MonitordStats>>>,
-    output_stats: bool,
-    maybe_connection: Option<zbus::Connection>,
+    options: StatCollectorOptions,
 ) -> Result<Option<zbus::Connection>, MonitordError> {
+    let StatCollectorOptions {
+        locked_stats: maybe_locked_stats,
+        connection: maybe_connection,
+        collectors,
+        sinks: extra_sinks,
+    } = options;
     let mut collect_interval_ms: u128 = 0;
     if config.monitord.daemon {
         collect_interval_ms = (config.monitord.daemon_stats_refresh_secs * 1000).into();
@@ -222,6 +342,8 @@ pub async fn stat_collector(
         maybe_locked_stats.unwrap_or(Arc::new(RwLock::new(MonitordStats::default())));
     let locked_machine_stats: Arc<RwLock<MachineStats>> =
         Arc::new(RwLock::new(MachineStats::default()));
+    let locked_extra_stats: Arc<RwLock<BTreeMap<String, serde_json::Value>>> =
+        Arc::new(RwLock::new(BTreeMap::new()));
     let cached_machine_connections: Arc<tokio::sync::Mutex<machines::MachineConnections>> =
         Arc::new(tokio::sync::Mutex::new(HashMap::new()));
     std::env::set_var("DBUS_SYSTEM_BUS_ADDRESS", &config.monitord.dbus_address);
@@ -229,6 +351,122 @@ pub async fn stat_collector(
     let mut join_set: tokio::task::JoinSet<TimedCollectorOutput> = tokio::task::JoinSet::new();
     let mut had_error;
 
+    // Serve the shared stats to Prometheus scrapers for the life of the daemon
+    if let Some(listen_address) = config.monitord.prometheus_listen_address {
+        if config.monitord.daemon {
+            let listener = prometheus::bind(listen_address).await?;
+            info!(
+                "Serving Prometheus metrics on http://{}{}",
+                listen_address,
+                prometheus::METRICS_PATH
+            );
+            tokio::spawn(prometheus::serve_metrics(
+                listener,
+                locked_monitord_stats.clone(),
+            ));
+        } else {
+            warn!("prometheus_listen_address is only used in daemon mode. Ignoring");
+        }
+    }
+
+    // Follow systemd signals between runs to count unit state transitions and
+    // keep the unit stats current
+    let mut unit_event_state = None;
+    if config.units.event_tracking {
+        if config.monitord.daemon {
+            let state = Arc::new(RwLock::new(unit_events::UnitEventState::new(&config.units)));
+            let connection = sdc.clone();
+            let state_clone = state.clone();
+            let stats_clone = locked_monitord_stats.clone();
+            tokio::spawn(async move {
+                if let Err(err) =
+                    unit_events::track_unit_events(connection, state_clone, stats_clone).await
+                {
+                    error!("Unit event tracking stopped: {:?}", err);
+                }
+            });
+            unit_event_state = Some(state);
+        } else {
+            warn!("units event_tracking is only used in daemon mode. Ignoring");
+        }
+    }
+
+    // Follow systemd job signals between runs to time jobs
+    let job_tracker = Arc::new(tokio::sync::Mutex::new(jobs::JobTracker::default()));
+    if config.jobs.enabled {
+        if config.monitord.daemon {
+            let connection = sdc.clone();
+            let tracker_clone = job_tracker.clone();
+            tokio::spawn(async move {
+                if let Err(err) = jobs::track_jobs(connection, tracker_clone).await {
+                    error!("Job tracking stopped: {:?}", err);
+                }
+            });
+        } else {
+            warn!("jobs results, durations and stuck jobs need daemon mode. Only reporting queued jobs");
+        }
+    }
+
+    // Built-in sinks run first, in config order, then any from the caller
+    let mut sinks: Vec<Box<dyn sink::Sink>> = Vec::new();
+    if config.stdout.enabled {
+        sinks.push(Box::new(sink::StdoutSink::new(
+            &config.monitord.key_prefix,
+            config.monitord.output_format.clone(),
+        )));
+    }
+    if config.statsd.enabled {
+        sinks.push(Box::new(
+            statsd::StatsdSink::new(&config.statsd, &config.monitord.key_prefix).await?,
+        ));
+    }
+    if config.graphite.enabled {
+        sinks.push(Box::new(graphite::GraphiteSink::new(
+            &config.graphite,
+            &config.monitord.key_prefix,
+        )));
+    }
+    if config.textfile.enabled {
+        sinks.push(Box::new(sink::TextfileSink::new(
+            config.textfile.path.clone(),
+        )));
+    }
+
+    // Serve the shared stats over io.monitord.Stats for the life of the daemon
+    if config.varlink_server.enabled {
+        if config.monitord.daemon {
+            sinks.push(Box::new(
+                varlink_server::serve(
+                    config.varlink_server.socket_path.clone(),
+                    locked_monitord_stats.clone(),
+                )
+                .await?,
+            ));
+            info!(
+                "Serving {} on {}",
+                varlink_server::INTERFACE,
+                config.varlink_server.socket_path.display()
+            );
+        } else {
+            warn!("varlink-server is only used in daemon mode. Ignoring");
+        }
+    }
+
+    // Publish stats on the system bus as io.monitord1 for the life of the daemon
+    if config.dbus_service.enabled {
+        if config.monitord.daemon {
+            sinks.push(Box::new(dbus_service::register(&sdc).await?));
+            info!(
+                "Publishing stats on D-Bus as {} at {}",
+                dbus_service::BUS_NAME,
+                dbus_service::OBJECT_PATH
+            );
+        } else {
+            warn!("dbus-service is only used in daemon mode. Ignoring");
+        }
+    }
+    sinks.extend(extra_sinks);
+
     loop {
         let collect_start_time = Instant::now();
         // Kept alive for the whole iteration so its reported duration covers
@@ -237,6 +475,9 @@ pub async fn stat_collector(
         let run_span = tracing::info_span!("stat_collector_run");
         let run_guard = run_span.enter();
         info!("Starting stat collection run");
+        // GetAll calls made outside the units collector this run
+        let locked_boot_get_all_timings: Arc<RwLock<dbus::properties::GetAllTimings>> =
+            Arc::new(RwLock::new(dbus::properties::GetAllTimings::default()));
 
         // Always collect systemd version
 
@@ -299,6 +540,85 @@ pub async fn stat_collector(
             );
         }
 
+        if config.manager.enabled {
+            spawn_timed(
+                &mut join_set,
+                "manager",
+                collect_start_time,
+                crate::manager::update_manager_stats(sdc.clone(), locked_machine_stats.clone()),
+            );
+        }
+
+        if config.manager_defaults.enabled {
+            spawn_timed(
+                &mut join_set,
+                "manager_defaults",
+                collect_start_time,
+                crate::manager_defaults::update_manager_defaults_stats(
+                    sdc.clone(),
+                    locked_machine_stats.clone(),
+                ),
+            );
+        }
+
+        if config.tainted.enabled {
+            spawn_timed(
+                &mut join_set,
+                "tainted",
+                collect_start_time,
+                crate::tainted::update_tainted_stats(sdc.clone(), locked_machine_stats.clone()),
+            );
+        }
+
+        if config.watchdog.enabled {
+            spawn_timed(
+                &mut join_set,
+                "watchdog",
+                collect_start_time,
+                crate::watchdog::update_watchdog_stats(sdc.clone(), locked_machine_stats.clone()),
+            );
+        }
+
+        if config.oom.enabled {
+            spawn_timed(
+                &mut join_set,
+                "oom",
+                collect_start_time,
+                crate::oom::update_oom_stats(
+                    Arc::clone(&config),
+                    sdc.clone(),
+                    locked_machine_stats.clone(),
+                ),
+            );
+        }
+
+        if config.pressure.enabled {
+            spawn_timed(
+                &mut join_set,
+                "pressure",
+                collect_start_time,
+                crate::pressure::update_pressure_stats(
+                    Arc::clone(&config),
+                    String::new(),
+                    locked_machine_stats.clone(),
+                ),
+            );
+        }
+
+        if config.slices.enabled {
+            spawn_timed(
+                &mut join_set,
+                "slices",
+                collect_start_time,
+                crate::slices::update_slices_stats(
+                    Arc::clone(&config),
+                    sdc.clone(),
+                    String::new(),
+                    locked_machine_stats.clone(),
+                ),
+            );
+        }
+
         // Run service collectors if there are services listed in config
         if config.units.enabled {
             let config_clone = Arc::clone(&config);
@@ -326,6 +646,13 @@ pub async fn stat_collector(
                                         timer_stats.timer_persistent_units;
                                     ms.units.timer_remain_after_elapse =
                                         timer_stats.timer_remain_after_elapse;
+                                    let timings = &timer_stats.collection_timings;
+                                    ms.units.collection_timings.timer_dbus_fetches =
+                                        timings.timer_dbus_fetches;
+                                    ms.units.collection_timings.properties_get_all_calls =
+                                        timings.properties_get_all_calls;
+                                    ms.units.collection_timings.properties_get_all_ms =
+                                        timings.properties_get_all_ms;
                                 }
                                 Err(err) => {
                                     warn!("Varlink timer stats (D-Bus fallback) failed: {:?}", err);
@@ -387,6 +714,46 @@ pub async fn stat_collector(
                     Arc::clone(&config),
                     sdc.clone(),
                     locked_machine_stats.clone(),
+                    locked_boot_get_all_timings.clone(),
+                ),
+            );
+        }
+
+        if config.boot_timeline.enabled {
+            spawn_timed(
+                &mut join_set,
+                "boot_timeline",
+                collect_start_time,
+                crate::boot_timeline::update_boot_timeline_stats(
+                    sdc.clone(),
+                    locked_machine_stats.clone(),
+                ),
+            );
+        }
+
+        if config.boot_critical_chain.enabled {
+            spawn_timed(
+                &mut join_set,
+                "boot_critical_chain",
+                collect_start_time,
+                crate::boot_critical_chain::update_boot_critical_chain_stats(
+                    Arc::clone(&config),
+                    sdc.clone(),
+                    locked_machine_stats.clone(),
+                ),
+            );
+        }
+
+        if config.boot_history.enabled {
+            spawn_timed(
+                &mut join_set,
+                "boot_history",
+                collect_start_time,
+                crate::boot_history::update_boot_history_stats(
+                    Arc::clone(&config),
+                    sdc.clone(),
+                    locked_machine_stats.clone(),
+                    locked_boot_get_all_timings.clone(),
                 ),
             );
         }
@@ -405,6 +772,46 @@ pub async fn stat_collector(
             );
         }
 
+        if config.jobs.enabled {
+            spawn_timed(
+                &mut join_set,
+                "jobs",
+                collect_start_time,
+                crate::jobs::update_job_stats(
+                    sdc.clone(),
+                    job_tracker.clone(),
+                    Duration::from_secs(config.jobs.stuck_job_threshold_secs),
+                    locked_machine_stats.clone(),
+                ),
+            );
+        }
+
+        for custom_collector in collectors.enabled(&config) {
+            let custom_collector = Arc::clone(custom_collector);
+            let name = custom_collector.name().to_string();
+            let config_clone = Arc::clone(&config);
+            let sdc_clone = sdc.clone();
+            let stats_clone = locked_extra_stats.clone();
+            spawn_timed(
+                &mut join_set,
+                name.clone(),
+                collect_start_time,
+                async move {
+                    match custom_collector.collect(&sdc_clone, &config_clone).await {
+                        Ok(value) => {
+                            stats_clone.write().await.insert(name, value);
+                            Ok(())
+                        }
+                        Err(err) => {
+                            // Don't keep reporting the last successful run's value
+                            stats_clone.write().await.remove(&name);
+                            Err(err)
+                        }
+                    }
+                },
+            );
+        }
+
         if join_set.len() == 1 {
             warn!("No collectors except systemd version scheduled to run. Exiting");
         }
@@ -461,35 +868,58 @@ pub async fn stat_collector(
             );
         }
 
-        {
+        // Sinks emit from a copy so slow network sinks don't hold the shared
+        // stats lock, blocking scrapers and the next run's write
+        let stats_snapshot = {
             // Update monitord stats with machine stats
             let mut monitord_stats = locked_monitord_stats.write().await;
             let machine_stats = locked_machine_stats.read().await;
             monitord_stats.pid1 = machine_stats.pid1.clone();
             monitord_stats.networkd = machine_stats.networkd.clone();
             monitord_stats.system_state = machine_stats.system_state;
+            monitord_stats.manager = machine_stats.manager.clone();
+            monitord_stats.manager_defaults = machine_stats.manager_defaults.clone();
+            monitord_stats.tainted = machine_stats.tainted.clone();
+            monitord_stats.watchdog = machine_stats.watchdog.clone();
+            monitord_stats.oom = machine_stats.oom.clone();
+            monitord_stats.pressure = machine_stats.pressure.clone();
+            monitord_stats.slices = machine_stats.slices.clone();
             monitord_stats.version = machine_stats.version.clone();
             monitord_stats.units = machine_stats.units.clone();
+            if let Some(state) = unit_event_state.as_ref() {
+                state.write().await.apply(&mut monitord_stats.units);
+            }
+            // The units collector replaces its timings every run, so the boot
+            // collectors' GetAll calls are added on here
+            let boot_get_all_timings = locked_boot_get_all_timings.read().await;
+            let collection_timings = &mut monitord_stats.units.collection_timings;
+            collection_timings.properties_get_all_calls += boot_get_all_timings.calls;
+            collection_timings.properties_get_all_ms += boot_get_all_timings.elapsed_ms;
             monitord_stats.dbus_stats = machine_stats.dbus_stats.clone();
             monitord_stats.boot_blame = machine_stats.boot_blame.clone();
+            monitord_stats.boot_timeline = machine_stats.boot_timeline.clone();
+            monitord_stats.boot_critical_chain = machine_stats.boot_critical_chain.clone();
+            monitord_stats.boot_history = machine_stats.boot_history.clone();
             monitord_stats.verify_stats = machine_stats.verify_stats.clone();
+            monitord_stats.jobs = machine_stats.jobs.clone();
+            monitord_stats.extra = locked_extra_stats.read().await.clone();
             set_stat_collection_run_time(&mut monitord_stats, elapsed_runtime);
             monitord_stats.collector_timings = timings;
-        }
+            monitord_stats.clone()
+        };
 
         info!("stat collection run took {}ms", elapsed_runtime_ms);
-        if output_stats {
-            let monitord_stats = locked_monitord_stats.read().await;
-            print_stats(
-                &config.monitord.key_prefix,
-                &config.monitord.output_format,
-                &monitord_stats,
-            );
+        for sink in sinks.iter_mut() {
+            if let Err(err) = sink.emit(&stats_snapshot).await {
+                error!("Sink '{}' failure: {:?}", sink.name(), err);
+            }
         }
         if !config.monitord.daemon {
             break;
         }
-        let sleep_time_ms = collect_interval_ms - elapsed_runtime_ms;
+        // Count the time spent in sinks too so runs stay on the refresh interval
+        let sleep_time_ms =
+            collect_interval_ms.saturating_sub(collect_start_time.elapsed().as_millis());
         info!("stat collection sleeping for {}s 😴", sleep_time_ms / 1000);
         tokio::time::sleep(Duration::from_millis(
             sleep_time_ms
@@ -226,6 +226,44 @@ pub async fn update_machines_stats(
             ));
         }
 
+        if config.manager.enabled {
+            join_set.spawn(crate::manager::update_manager_stats(
+                sdc.clone(),
+                locked_machine_stats.clone(),
+            ));
+        }
+
+        if config.manager_defaults.enabled {
+            join_set.spawn(crate::manager_defaults::update_manager_defaults_stats(
+                sdc.clone(),
+                locked_machine_stats.clone(),
+            ));
+        }
+
+        if config.tainted.enabled {
+            join_set.spawn(crate::tainted::update_tainted_stats(
+                sdc.clone(),
+                locked_machine_stats.clone(),
+            ));
+        }
+
+        if config.pressure.enabled {
+            join_set.spawn(crate::pressure::update_pressure_stats(
+                Arc::clone(&config),
+                format!("/proc/{}/root", leader_pid),
+                locked_machine_stats.clone(),
+            ));
+        }
+
+        if config.slices.enabled {
+            join_set.spawn(crate::slices::update_slices_stats(
+                Arc::clone(&config),
+                sdc.clone(),
+                format!("/proc/{}/root", leader_pid),
+                locked_machine_stats.clone(),
+            ));
+        }
+
         join_set.spawn(crate::system::update_version(
             sdc.clone(),
             locked_machine_stats.clone(),
@@ -38,6 +38,10 @@ async fn main() -> anyhow::Result<()> {
         .load(args.config)
         .map_err(|e| anyhow::anyhow!("Config error: {:?}", e))?;
 
-    monitord::stat_collector(config.try_into()?, None, true, None).await?;
+    monitord::stat_collector(
+        config.try_into()?,
+        monitord::StatCollectorOptions::default(),
+    )
+    .await?;
     Ok(())
 }
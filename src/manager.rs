@@ -0,0 +1,60 @@
+//! # manager module
+//!
+//! Counters the systemd manager keeps itself. `nfailed_jobs` counts every
+//! failed job since boot, which counting `ListUnits` can't show once the
+//! unit recovers.
+
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::Instrument;
+
+use crate::dbus::zbus_systemd::ManagerProxy;
+use crate::MachineStats;
+
+/// systemd manager properties
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ManagerStats {
+    /// Jobs that failed since boot
+    pub nfailed_jobs: u64,
+    /// Units in the failed state
+    pub nfailed_units: u64,
+    /// Jobs installed since boot
+    pub ninstalled_jobs: u64,
+    /// Jobs currently queued
+    pub njobs: u64,
+    /// Unit names currently loaded, including aliases
+    pub nnames: u64,
+    /// Boot progress from 0.0 to 1.0. 1.0 once startup has finished.
+    pub progress: f64,
+}
+
+pub async fn get_manager_stats(connection: &zbus::Connection) -> zbus::Result<ManagerStats> {
+    let manager = ManagerProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    Ok(ManagerStats {
+        nfailed_jobs: manager.nfailed_jobs().await?.into(),
+        nfailed_units: manager.nfailed_units().await?.into(),
+        ninstalled_jobs: manager.ninstalled_jobs().await?.into(),
+        njobs: manager.njobs().await?.into(),
+        nnames: manager.nnames().await?.into(),
+        progress: manager.progress().await?,
+    })
+}
+
+/// Async wrapper than can update manager stats when passed a locked struct.
+/// The D-Bus calls run before the shared write lock is taken.
+pub async fn update_manager_stats(
+    connection: zbus::Connection,
+    locked_machine_stats: Arc<RwLock<MachineStats>>,
+) -> anyhow::Result<()> {
+    let manager_stats = get_manager_stats(&connection)
+        .instrument(tracing::debug_span!("manager_dbus_calls"))
+        .await
+        .map_err(|e| anyhow::anyhow!("Error getting manager stats: {:?}", e))?;
+    let mut machine_stats = locked_machine_stats.write().await;
+    machine_stats.manager = Some(manager_stats);
+    Ok(())
+}
@@ -0,0 +1,255 @@
+//! # manager_defaults module
+//!
+//! Snapshot of the systemd manager `Default*` properties set by
+//! `system.conf` (`DefaultLimitNOFILE=`, `DefaultTasksMax=`,
+//! `DefaultTimeoutStartSec=`, ...) plus a stable hash of the whole set, so
+//! hosts that drifted from the fleet standard can be found by comparing one
+//! value. The set of properties depends on the systemd version, so only
+//! compare hashes between hosts running the same version.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::debug;
+use zbus::names::InterfaceName;
+use zvariant::OwnedValue;
+use zvariant::Value;
+
+use crate::MachineStats;
+
+const MANAGER_INTERFACE: &str = "org.freedesktop.systemd1.Manager";
+const DEFAULT_PROPERTY_PREFIX: &str = "Default";
+
+/// One manager default. Numbers are kept as D-Bus returns them so
+/// `infinity` stays `u64::MAX`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum ManagerDefaultValue {
+    Bool(bool),
+    UInt(u64),
+    Int(i64),
+    String(String),
+}
+
+impl fmt::Display for ManagerDefaultValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManagerDefaultValue::Bool(b) => write!(f, "{b}"),
+            ManagerDefaultValue::UInt(u) => write!(f, "{u}"),
+            ManagerDefaultValue::Int(i) => write!(f, "{i}"),
+            ManagerDefaultValue::String(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl ManagerDefaultValue {
+    fn from_dbus(value: &Value<'_>) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(ManagerDefaultValue::Bool(*b)),
+            Value::U8(u) => Some(ManagerDefaultValue::UInt((*u).into())),
+            Value::U16(u) => Some(ManagerDefaultValue::UInt((*u).into())),
+            Value::U32(u) => Some(ManagerDefaultValue::UInt((*u).into())),
+            Value::U64(u) => Some(ManagerDefaultValue::UInt(*u)),
+            Value::I16(i) => Some(ManagerDefaultValue::Int((*i).into())),
+            Value::I32(i) => Some(ManagerDefaultValue::Int((*i).into())),
+            Value::I64(i) => Some(ManagerDefaultValue::Int(*i)),
+            Value::Str(s) => Some(ManagerDefaultValue::String(s.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// systemd manager defaults
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ManagerDefaultsStats {
+    /// FNV-1a 64 bit hash of every value as 16 hex digits
+    pub hash: String,
+    /// Property name without the `Default` prefix in snake case (e.g.
+    /// `DefaultLimitNOFILE` is `limit_nofile`) to value
+    pub values: BTreeMap<String, ManagerDefaultValue>,
+}
+
+impl ManagerDefaultsStats {
+    fn new(values: BTreeMap<String, ManagerDefaultValue>) -> Self {
+        ManagerDefaultsStats {
+            hash: format!("{:016x}", hash_values(&values)),
+            values,
+        }
+    }
+}
+
+/// `LimitNOFILESoft` -> `limit_nofilesoft`, the same as zbus names property getters
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    let mut prev_lower_or_digit = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && prev_lower_or_digit {
+            snake.push('_');
+        }
+        prev_lower_or_digit = c.is_ascii_lowercase() || c.is_ascii_digit();
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// FNV-1a over sorted `name=value` lines. Hand rolled as std's hashers are
+/// not guaranteed to be stable between Rust releases.
+fn hash_values(values: &BTreeMap<String, ManagerDefaultValue>) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+    let mut hash = FNV_OFFSET_BASIS;
+    for (name, value) in values {
+        for byte in format!("{name}={value}\n").bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+fn parse_manager_defaults(properties: &HashMap<String, OwnedValue>) -> ManagerDefaultsStats {
+    let mut values = BTreeMap::new();
+    for (name, value) in properties {
+        let Some(default_name) = name.strip_prefix(DEFAULT_PROPERTY_PREFIX) else {
+            continue;
+        };
+        match ManagerDefaultValue::from_dbus(value) {
+            Some(value) => {
+                values.insert(to_snake_case(default_name), value);
+            }
+            None => debug!("Skipping manager default {} of unsupported type", name),
+        }
+    }
+    ManagerDefaultsStats::new(values)
+}
+
+/// Fetch every manager property in one `GetAll` call rather than one round
+/// trip per property. Properties missing from older systemd versions are
+/// simply absent.
+pub async fn get_manager_defaults_stats(
+    connection: &zbus::Connection,
+) -> anyhow::Result<ManagerDefaultsStats> {
+    let properties_proxy = zbus::fdo::PropertiesProxy::builder(connection)
+        .destination("org.freedesktop.systemd1")?
+        .path("/org/freedesktop/systemd1")?
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let properties = properties_proxy
+        .get_all(InterfaceName::from_static_str(MANAGER_INTERFACE)?)
+        .await?;
+    Ok(parse_manager_defaults(&properties))
+}
+
+/// Async wrapper than can update manager defaults when passed a locked struct
+pub async fn update_manager_defaults_stats(
+    connection: zbus::Connection,
+    locked_machine_stats: Arc<RwLock<MachineStats>>,
+) -> anyhow::Result<()> {
+    let manager_defaults_stats = get_manager_defaults_stats(&connection)
+        .await
+        .map_err(|e| anyhow::anyhow!("Error getting manager defaults: {:?}", e))?;
+    let mut machine_stats = locked_machine_stats.write().await;
+    machine_stats.manager_defaults = Some(manager_defaults_stats);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!("limit_nofile", to_snake_case("LimitNOFILE"));
+        assert_eq!("limit_nofilesoft", to_snake_case("LimitNOFILESoft"));
+        assert_eq!("oomscore_adjust", to_snake_case("OOMScoreAdjust"));
+        assert_eq!("timeout_start_usec", to_snake_case("TimeoutStartUSec"));
+        assert_eq!("cpuaccounting", to_snake_case("CPUAccounting"));
+    }
+
+    #[test]
+    fn test_parse_manager_defaults() {
+        let properties = HashMap::from([
+            (
+                String::from("DefaultLimitNOFILE"),
+                OwnedValue::from(524288_u64),
+            ),
+            (
+                String::from("DefaultMemoryAccounting"),
+                OwnedValue::from(true),
+            ),
+            (
+                String::from("DefaultOOMPolicy"),
+                OwnedValue::try_from(Value::from("stop")).expect("owned str"),
+            ),
+            (
+                String::from("DefaultOOMScoreAdjust"),
+                OwnedValue::from(-100_i32),
+            ),
+            (
+                String::from("DefaultStartLimitBurst"),
+                OwnedValue::from(5_u32),
+            ),
+            // Not a default
+            (String::from("NNames"), OwnedValue::from(42_u32)),
+        ]);
+        let stats = parse_manager_defaults(&properties);
+        assert_eq!(
+            BTreeMap::from([
+                (
+                    String::from("limit_nofile"),
+                    ManagerDefaultValue::UInt(524288)
+                ),
+                (
+                    String::from("memory_accounting"),
+                    ManagerDefaultValue::Bool(true)
+                ),
+                (
+                    String::from("oompolicy"),
+                    ManagerDefaultValue::String(String::from("stop"))
+                ),
+                (
+                    String::from("oomscore_adjust"),
+                    ManagerDefaultValue::Int(-100)
+                ),
+                (
+                    String::from("start_limit_burst"),
+                    ManagerDefaultValue::UInt(5)
+                ),
+            ]),
+            stats.values
+        );
+        assert_eq!(16, stats.hash.len());
+    }
+
+    #[test]
+    fn test_hash_stable() {
+        let values = BTreeMap::from([
+            (
+                String::from("limit_nofile"),
+                ManagerDefaultValue::UInt(1024),
+            ),
+            (
+                String::from("oompolicy"),
+                ManagerDefaultValue::String(String::from("stop")),
+            ),
+        ]);
+        // Pinned so a change to the hash input is caught before fleets see
+        // every host as drifted
+        assert_eq!(0xc61677dee76bce1e, hash_values(&values));
+        assert_eq!(
+            hash_values(&values),
+            hash_values(&values.clone().into_iter().rev().collect())
+        );
+        assert_eq!(0xcbf29ce484222325, hash_values(&BTreeMap::new()));
+        let mut drifted = values.clone();
+        drifted.insert(
+            String::from("limit_nofile"),
+            ManagerDefaultValue::UInt(524288),
+        );
+        assert_ne!(hash_values(&values), hash_values(&drifted));
+    }
+}
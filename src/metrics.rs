@@ -0,0 +1,1075 @@
+//! # metrics module
+//!
+//! Walk `MonitordStats` once for the sinks that render individual labelled
+//! samples (Prometheus and InfluxDB line protocol). Each format implements
+//! `MetricWriter` and only decides how a sample is spelled, so a section can't
+//! go missing from one of them. Host and machine stats are walked through the
+//! same `HostStats` view so containers get every section the host does.
+
+use std::collections::BTreeMap;
+
+use crate::dbus_stats;
+use crate::networkd;
+use crate::pid1;
+use crate::units;
+use crate::MachineStats;
+use crate::MonitordStats;
+
+pub(crate) type Labels<'a> = Vec<(&'a str, &'a str)>;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum MetricKind {
+    Counter,
+    Gauge,
+}
+
+/// Typed sample value. Formats without a type system render them all as floats.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum MetricValue {
+    Boolean(bool),
+    Float(f64),
+    Integer(i64),
+    Unsigned(u64),
+    String(String),
+}
+
+impl MetricValue {
+    /// Convert a serde value. Strings and nested values are skipped.
+    pub(crate) fn from_json(value: &serde_json::Value) -> Option<Self> {
+        match value {
+            serde_json::Value::Bool(b) => Some(MetricValue::Boolean(*b)),
+            serde_json::Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    Some(MetricValue::Integer(i))
+                } else if let Some(u) = n.as_u64() {
+                    Some(MetricValue::Unsigned(u))
+                } else {
+                    n.as_f64().map(MetricValue::Float)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Numeric value of the sample, `None` for strings
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            MetricValue::Boolean(b) => Some(if *b { 1.0 } else { 0.0 }),
+            MetricValue::Float(f) => Some(*f),
+            MetricValue::Integer(i) => Some(*i as f64),
+            MetricValue::Unsigned(u) => Some(*u as f64),
+            MetricValue::String(_) => None,
+        }
+    }
+}
+
+impl From<bool> for MetricValue {
+    fn from(value: bool) -> Self {
+        MetricValue::Boolean(value)
+    }
+}
+
+impl From<f64> for MetricValue {
+    fn from(value: f64) -> Self {
+        MetricValue::Float(value)
+    }
+}
+
+impl From<u64> for MetricValue {
+    fn from(value: u64) -> Self {
+        MetricValue::Unsigned(value)
+    }
+}
+
+impl From<u32> for MetricValue {
+    fn from(value: u32) -> Self {
+        MetricValue::Unsigned(value.into())
+    }
+}
+
+/// A metrics format. Every sample is named by its `subsystem` and `field`.
+pub(crate) trait MetricWriter {
+    /// Add one sample
+    fn push(
+        &mut self,
+        subsystem: &str,
+        field: &str,
+        help: &str,
+        kind: MetricKind,
+        labels: &[(&str, &str)],
+        value: MetricValue,
+    );
+
+    /// Add a string property of `subsystem`, e.g. the systemd version
+    fn push_info(
+        &mut self,
+        subsystem: &str,
+        field: &str,
+        help: &str,
+        labels: &[(&str, &str)],
+        value: &str,
+    );
+
+    /// Add every numeric or boolean field of a serializable stats struct.
+    /// Nested maps, strings and `None`s are skipped.
+    fn push_struct_fields<T: serde::Serialize>(
+        &mut self,
+        subsystem: &str,
+        help_subject: &str,
+        stats: &T,
+        labels: &[(&str, &str)],
+    ) {
+        let Ok(serde_json::Value::Object(map)) = serde_json::to_value(stats) else {
+            return;
+        };
+        for (field_name, value) in map {
+            let Some(value) = MetricValue::from_json(&value) else {
+                continue;
+            };
+            let kind = match crate::json::is_counter_field(&field_name) {
+                true => MetricKind::Counter,
+                false => MetricKind::Gauge,
+            };
+            self.push(
+                subsystem,
+                &field_name,
+                &format!("{help_subject} {field_name}"),
+                kind,
+                labels,
+                value,
+            );
+        }
+    }
+}
+
+pub(crate) fn with_label<'a>(
+    labels: &[(&'a str, &'a str)],
+    key: &'a str,
+    value: &'a str,
+) -> Labels<'a> {
+    let mut labels = labels.to_vec();
+    labels.push((key, value));
+    labels
+}
+
+/// The sections collected for the host and for every machine
+pub(crate) struct HostStats<'a> {
+    networkd: &'a networkd::NetworkdState,
+    pid1: &'a Option<pid1::Pid1Stats>,
+    system_state: crate::system::SystemdSystemState,
+    manager: &'a Option<crate::manager::ManagerStats>,
+    manager_defaults: &'a Option<crate::manager_defaults::ManagerDefaultsStats>,
+    tainted: &'a Option<crate::tainted::TaintedStats>,
+    watchdog: &'a Option<crate::watchdog::WatchdogStats>,
+    oom: &'a Option<crate::oom::OomStats>,
+    pressure: &'a Option<crate::pressure::PressureStats>,
+    slices: &'a Option<crate::slices::SlicesStats>,
+    units: &'a units::SystemdUnitStats,
+    version: &'a crate::system::SystemdVersion,
+    dbus_stats: &'a Option<dbus_stats::DBusStats>,
+    boot_blame: &'a Option<crate::boot::BootBlameStats>,
+    boot_timeline: &'a Option<crate::boot_timeline::BootTimelineStats>,
+    boot_critical_chain: &'a Option<crate::boot_critical_chain::BootCriticalChainStats>,
+    boot_history: &'a Option<crate::boot_history::BootHistoryStats>,
+    verify_stats: &'a Option<crate::verify::VerifyStats>,
+    jobs: &'a Option<crate::jobs::JobStats>,
+}
+
+impl<'a> From<&'a MonitordStats> for HostStats<'a> {
+    fn from(stats: &'a MonitordStats) -> Self {
+        HostStats {
+            networkd: &stats.networkd,
+            pid1: &stats.pid1,
+            system_state: stats.system_state,
+            manager: &stats.manager,
+            manager_defaults: &stats.manager_defaults,
+            tainted: &stats.tainted,
+            watchdog: &stats.watchdog,
+            oom: &stats.oom,
+            pressure: &stats.pressure,
+            slices: &stats.slices,
+            units: &stats.units,
+            version: &stats.version,
+            dbus_stats: &stats.dbus_stats,
+            boot_blame: &stats.boot_blame,
+            boot_timeline: &stats.boot_timeline,
+            boot_critical_chain: &stats.boot_critical_chain,
+            boot_history: &stats.boot_history,
+            verify_stats: &stats.verify_stats,
+            jobs: &stats.jobs,
+        }
+    }
+}
+
+impl<'a> From<&'a MachineStats> for HostStats<'a> {
+    fn from(stats: &'a MachineStats) -> Self {
+        HostStats {
+            networkd: &stats.networkd,
+            pid1: &stats.pid1,
+            system_state: stats.system_state,
+            manager: &stats.manager,
+            manager_defaults: &stats.manager_defaults,
+            tainted: &stats.tainted,
+            watchdog: &stats.watchdog,
+            oom: &stats.oom,
+            pressure: &stats.pressure,
+            slices: &stats.slices,
+            units: &stats.units,
+            version: &stats.version,
+            dbus_stats: &stats.dbus_stats,
+            boot_blame: &stats.boot_blame,
+            boot_timeline: &stats.boot_timeline,
+            boot_critical_chain: &stats.boot_critical_chain,
+            boot_history: &stats.boot_history,
+            verify_stats: &stats.verify_stats,
+            jobs: &stats.jobs,
+        }
+    }
+}
+
+fn add_networkd<W: MetricWriter>(
+    writer: &mut W,
+    networkd_stats: &networkd::NetworkdState,
+    labels: &Labels,
+) {
+    writer.push(
+        "networkd",
+        "managed_interfaces",
+        "Number of interfaces managed by systemd-networkd",
+        MetricKind::Gauge,
+        labels,
+        networkd_stats.managed_interfaces.into(),
+    );
+    for interface in &networkd_stats.interfaces_state {
+        writer.push_struct_fields(
+            "networkd",
+            "systemd-networkd interface",
+            interface,
+            &with_label(labels, "interface", &interface.name),
+        );
+    }
+}
+
+fn add_pid1<W: MetricWriter>(
+    writer: &mut W,
+    optional_pid1_stats: &Option<pid1::Pid1Stats>,
+    labels: &Labels,
+) {
+    if let Some(pid1_stats) = optional_pid1_stats {
+        writer.push_struct_fields("pid1", "PID 1 process", pid1_stats, labels);
+    }
+}
+
+fn add_system<W: MetricWriter>(
+    writer: &mut W,
+    system_state: crate::system::SystemdSystemState,
+    version: &crate::system::SystemdVersion,
+    labels: &Labels,
+) {
+    writer.push(
+        "system",
+        "state",
+        "systemd manager SystemState as an integer (see README Metric Value Reference)",
+        MetricKind::Gauge,
+        labels,
+        (system_state as u64).into(),
+    );
+    writer.push_info(
+        "system",
+        "version",
+        "Running systemd version",
+        labels,
+        &version.to_string(),
+    );
+}
+
+fn add_manager<W: MetricWriter>(
+    writer: &mut W,
+    optional_manager_stats: &Option<crate::manager::ManagerStats>,
+    labels: &Labels,
+) {
+    if let Some(manager_stats) = optional_manager_stats {
+        writer.push_struct_fields("manager", "systemd manager", manager_stats, labels);
+    }
+}
+
+fn add_manager_defaults<W: MetricWriter>(
+    writer: &mut W,
+    optional_manager_defaults_stats: &Option<crate::manager_defaults::ManagerDefaultsStats>,
+    labels: &Labels,
+) {
+    let Some(manager_defaults_stats) = optional_manager_defaults_stats else {
+        return;
+    };
+    writer.push_struct_fields(
+        "manager_defaults",
+        "systemd manager default",
+        &manager_defaults_stats.values,
+        labels,
+    );
+    writer.push_info(
+        "manager_defaults",
+        "hash",
+        "Hash of all systemd manager defaults",
+        labels,
+        &manager_defaults_stats.hash,
+    );
+}
+
+fn add_tainted<W: MetricWriter>(
+    writer: &mut W,
+    optional_tainted_stats: &Option<crate::tainted::TaintedStats>,
+    labels: &Labels,
+) {
+    let Some(tainted_stats) = optional_tainted_stats else {
+        return;
+    };
+    writer.push_struct_fields("tainted", "systemd Tainted flag", tainted_stats, labels);
+    writer.push(
+        "tainted",
+        "unknown",
+        "Number of systemd Tainted flags monitord doesn't know",
+        MetricKind::Gauge,
+        labels,
+        (tainted_stats.unknown.len() as u64).into(),
+    );
+}
+
+fn add_watchdog<W: MetricWriter>(
+    writer: &mut W,
+    optional_watchdog_stats: &Option<crate::watchdog::WatchdogStats>,
+    labels: &Labels,
+) {
+    let Some(watchdog_stats) = optional_watchdog_stats else {
+        return;
+    };
+    writer.push_struct_fields(
+        "watchdog",
+        "PID 1 hardware watchdog",
+        watchdog_stats,
+        &with_label(labels, "device", &watchdog_stats.device),
+    );
+}
+
+fn add_oom<W: MetricWriter>(
+    writer: &mut W,
+    optional_oom_stats: &Option<crate::oom::OomStats>,
+    labels: &Labels,
+) {
+    let Some(oom_stats) = optional_oom_stats else {
+        return;
+    };
+    for (service_name, service_oom_stats) in &oom_stats.services {
+        writer.push_struct_fields(
+            "oom_service",
+            "systemd service cgroup memory.events",
+            service_oom_stats,
+            &with_label(labels, "unit", service_name),
+        );
+    }
+    writer.push_struct_fields(
+        "oom_total",
+        "Tracked services cgroup memory.events",
+        &oom_stats.total,
+        labels,
+    );
+    if let Some(host_oom_kill) = oom_stats.host_oom_kill {
+        writer.push(
+            "oom",
+            "host_oom_kill",
+            "Processes killed by the OOM killer on the host since boot",
+            MetricKind::Counter,
+            labels,
+            host_oom_kill.into(),
+        );
+    }
+}
+
+/// One series per PSI line with `resource` (cpu/memory/io) and `kind`
+/// (some/full) labels
+fn add_cgroup_pressure<W: MetricWriter>(
+    writer: &mut W,
+    subsystem: &str,
+    help_subject: &str,
+    pressure: &crate::cgroup::CgroupPressure,
+    labels: &Labels,
+) {
+    for (resource, kind, line) in pressure.lines() {
+        let mut pressure_labels = with_label(labels, "resource", resource);
+        pressure_labels.push(("kind", kind));
+        writer.push_struct_fields(subsystem, help_subject, line, &pressure_labels);
+    }
+}
+
+fn add_pressure<W: MetricWriter>(
+    writer: &mut W,
+    optional_pressure_stats: &Option<crate::pressure::PressureStats>,
+    labels: &Labels,
+) {
+    let Some(pressure_stats) = optional_pressure_stats else {
+        return;
+    };
+    for (slice, pressure) in &pressure_stats.slices {
+        add_cgroup_pressure(
+            writer,
+            "slice_pressure",
+            "systemd slice cgroup pressure stall",
+            pressure,
+            &with_label(labels, "slice", slice),
+        );
+    }
+}
+
+fn add_slices<W: MetricWriter>(
+    writer: &mut W,
+    optional_slices_stats: &Option<crate::slices::SlicesStats>,
+    labels: &Labels,
+) {
+    let Some(slices_stats) = optional_slices_stats else {
+        return;
+    };
+    for (slice, slice_stats) in &slices_stats.slices {
+        let slice_labels = with_label(labels, "slice", slice);
+        writer.push_struct_fields(
+            "slices",
+            "systemd slice cgroup",
+            &slice_stats.usage,
+            &slice_labels,
+        );
+        add_cgroup_pressure(
+            writer,
+            "slices_pressure",
+            "systemd slice cgroup pressure stall",
+            &slice_stats.pressure,
+            &slice_labels,
+        );
+    }
+}
+
+fn add_unit_files_scope<W: MetricWriter>(
+    writer: &mut W,
+    scope_name: &str,
+    scope: &units::UnitFilesScope,
+    labels: &Labels,
+) {
+    for (kind, counts) in [
+        ("generated", &scope.generated),
+        ("transient", &scope.transient),
+    ] {
+        for (unit_type, count) in counts {
+            let mut unit_file_labels = with_label(labels, "scope", scope_name);
+            unit_file_labels.push(("kind", kind));
+            unit_file_labels.push(("unit_type", unit_type));
+            writer.push(
+                "unit_files",
+                "count",
+                "Number of generated or transient unit files by scope and unit type",
+                MetricKind::Gauge,
+                &unit_file_labels,
+                (*count).into(),
+            );
+        }
+    }
+}
+
+fn add_units<W: MetricWriter>(
+    writer: &mut W,
+    units_stats: &units::SystemdUnitStats,
+    labels: &Labels,
+) {
+    writer.push_struct_fields(
+        "units",
+        "systemd unit counter",
+        &crate::json::UnitCounters::from(units_stats),
+        labels,
+    );
+    add_unit_files_scope(writer, "root", &units_stats.unit_files.root, labels);
+    add_unit_files_scope(writer, "user", &units_stats.unit_files.user, labels);
+
+    for (service_name, service_stats) in &units_stats.service_stats {
+        let unit_labels = with_label(labels, "unit", service_name);
+        writer.push_struct_fields("service", "systemd service", service_stats, &unit_labels);
+        if let Some(pressure) = &service_stats.pressure {
+            add_cgroup_pressure(
+                writer,
+                "service_pressure",
+                "systemd service cgroup pressure stall",
+                pressure,
+                &unit_labels,
+            );
+        }
+    }
+    for (timer_name, timer_stats) in &units_stats.timer_stats {
+        let unit_labels = with_label(labels, "unit", timer_name);
+        writer.push_struct_fields("timer", "systemd timer", timer_stats, &unit_labels);
+    }
+    for (unit_name, unit_state_stats) in &units_stats.unit_states {
+        let unit_labels = with_label(labels, "unit", unit_name);
+        writer.push_struct_fields("unit", "systemd unit state", unit_state_stats, &unit_labels);
+    }
+    for (unit_name, transitions) in &units_stats.unit_state_transitions {
+        let unit_labels = with_label(labels, "unit", unit_name);
+        writer.push_struct_fields(
+            "unit_state_transitions",
+            "systemd unit signals since the previous output",
+            transitions,
+            &unit_labels,
+        );
+    }
+
+    let timings = &units_stats.collection_timings;
+    writer.push_struct_fields(
+        "units_collection",
+        "units collector timing",
+        timings,
+        labels,
+    );
+    for (rank, (unit_name, duration_ms)) in timings.slowest_units.iter().enumerate() {
+        let rank = rank.to_string();
+        let mut slowest_labels = with_label(labels, "rank", &rank);
+        slowest_labels.push(("unit", unit_name));
+        writer.push(
+            "units_collection",
+            "slowest_unit_ms",
+            "Slowest units by per-unit collection duration in milliseconds",
+            MetricKind::Gauge,
+            &slowest_labels,
+            (*duration_ms).into(),
+        );
+    }
+}
+
+fn add_dbus_stats<W: MetricWriter>(
+    writer: &mut W,
+    optional_dbus_stats: &Option<dbus_stats::DBusStats>,
+    labels: &Labels,
+) {
+    let Some(dbus_stats) = optional_dbus_stats else {
+        return;
+    };
+
+    let fields = [
+        // ignore serial
+        ("active_connections", dbus_stats.active_connections),
+        ("incomplete_connections", dbus_stats.incomplete_connections),
+        ("bus_names", dbus_stats.bus_names),
+        ("peak_bus_names", dbus_stats.peak_bus_names),
+        (
+            "peak_bus_names_per_connection",
+            dbus_stats.peak_bus_names_per_connection,
+        ),
+        ("match_rules", dbus_stats.match_rules),
+        ("peak_match_rules", dbus_stats.peak_match_rules),
+        (
+            "peak_match_rules_per_connection",
+            dbus_stats.peak_match_rules_per_connection,
+        ),
+        ("stale_fds", dbus_stats.stale_fds),
+    ];
+    for (field_name, value) in fields {
+        if let Some(val) = value {
+            writer.push(
+                "dbus",
+                field_name,
+                &format!("D-Bus daemon/broker {field_name}"),
+                MetricKind::Gauge,
+                labels,
+                val.into(),
+            );
+        }
+    }
+
+    if let Some(peer_accounting) = dbus_stats.peer_accounting() {
+        for peer in peer_accounting.values() {
+            let peer_labels = with_label(labels, "peer", peer.get_name());
+            let peer_fields = [
+                ("name_objects", peer.name_objects),
+                ("match_bytes", peer.match_bytes),
+                ("matches", peer.matches),
+                ("reply_objects", peer.reply_objects),
+                ("incoming_bytes", peer.incoming_bytes),
+                ("incoming_fds", peer.incoming_fds),
+                ("outgoing_bytes", peer.outgoing_bytes),
+                ("outgoing_fds", peer.outgoing_fds),
+                ("activation_request_bytes", peer.activation_request_bytes),
+                ("activation_request_fds", peer.activation_request_fds),
+            ];
+            for (field_name, value) in peer_fields {
+                if let Some(val) = value {
+                    writer.push(
+                        "dbus_peer",
+                        field_name,
+                        &format!("dbus-broker per-peer {field_name}"),
+                        MetricKind::Gauge,
+                        &peer_labels,
+                        val.into(),
+                    );
+                }
+            }
+        }
+    }
+
+    if let Some(cgroup_accounting) = dbus_stats.cgroup_accounting() {
+        for cgroup in cgroup_accounting.values() {
+            let cgroup_labels = with_label(labels, "cgroup", &cgroup.name);
+            let cgroup_fields = [
+                ("name_objects", cgroup.name_objects),
+                ("match_bytes", cgroup.match_bytes),
+                ("matches", cgroup.matches),
+                ("reply_objects", cgroup.reply_objects),
+                ("incoming_bytes", cgroup.incoming_bytes),
+                ("incoming_fds", cgroup.incoming_fds),
+                ("outgoing_bytes", cgroup.outgoing_bytes),
+                ("outgoing_fds", cgroup.outgoing_fds),
+                ("activation_request_bytes", cgroup.activation_request_bytes),
+                ("activation_request_fds", cgroup.activation_request_fds),
+            ];
+            for (field_name, value) in cgroup_fields {
+                if let Some(val) = value {
+                    writer.push(
+                        "dbus_cgroup",
+                        field_name,
+                        &format!("dbus-broker per-cgroup {field_name}"),
+                        MetricKind::Gauge,
+                        &cgroup_labels,
+                        val.into(),
+                    );
+                }
+            }
+        }
+    }
+
+    if let Some(user_accounting) = dbus_stats.user_accounting() {
+        for user in user_accounting.values() {
+            let user_labels = with_label(labels, "user", &user.username);
+            let user_fields = [
+                ("bytes", &user.bytes),
+                ("fds", &user.fds),
+                ("matches", &user.matches),
+                ("objects", &user.objects),
+            ];
+            for (field_name, value) in user_fields {
+                if let Some(val) = value {
+                    writer.push(
+                        "dbus_user",
+                        field_name,
+                        &format!("dbus-broker per-user {field_name} usage"),
+                        MetricKind::Gauge,
+                        &user_labels,
+                        val.get_usage().into(),
+                    );
+                }
+            }
+            if let Some(stale_fds) = user.stale_fds {
+                writer.push(
+                    "dbus_user",
+                    "stale_fds",
+                    "Stale pidfd descriptors held by the system dbus-broker",
+                    MetricKind::Gauge,
+                    &user_labels,
+                    stale_fds.into(),
+                );
+            }
+        }
+    }
+}
+
+fn add_boot_blame<W: MetricWriter>(
+    writer: &mut W,
+    optional_boot_blame: &Option<crate::boot::BootBlameStats>,
+    labels: &Labels,
+) {
+    let Some(boot_blame_stats) = optional_boot_blame else {
+        return;
+    };
+    for (unit_name, activation_time) in boot_blame_stats {
+        writer.push(
+            "boot_blame",
+            "seconds",
+            "Activation time of the slowest units at boot in seconds",
+            MetricKind::Gauge,
+            &with_label(labels, "unit", unit_name),
+            (*activation_time).into(),
+        );
+    }
+}
+
+fn add_boot_timeline<W: MetricWriter>(
+    writer: &mut W,
+    optional_boot_timeline: &Option<crate::boot_timeline::BootTimelineStats>,
+    labels: &Labels,
+) {
+    if let Some(boot_timeline) = optional_boot_timeline {
+        writer.push_struct_fields("boot_timeline", "Boot timeline", boot_timeline, labels);
+    }
+}
+
+fn add_boot_critical_chain<W: MetricWriter>(
+    writer: &mut W,
+    optional_boot_critical_chain: &Option<crate::boot_critical_chain::BootCriticalChainStats>,
+    labels: &Labels,
+) {
+    let Some(boot_critical_chain) = optional_boot_critical_chain else {
+        return;
+    };
+    let target_labels = with_label(labels, "target", &boot_critical_chain.target);
+    for hop in &boot_critical_chain.chain {
+        let hop_labels = with_label(&target_labels, "unit", &hop.unit);
+        writer.push(
+            "boot_critical_chain",
+            "activated_seconds",
+            "Seconds after userspace started that a critical chain unit became active",
+            MetricKind::Gauge,
+            &hop_labels,
+            hop.activated_secs.into(),
+        );
+        writer.push(
+            "boot_critical_chain",
+            "activation_seconds",
+            "Seconds a critical chain unit took to activate",
+            MetricKind::Gauge,
+            &hop_labels,
+            hop.activation_secs.into(),
+        );
+    }
+}
+
+fn add_boot_history<W: MetricWriter>(
+    writer: &mut W,
+    optional_boot_history: &Option<crate::boot_history::BootHistoryStats>,
+    labels: &Labels,
+) {
+    let Some(boot_history) = optional_boot_history else {
+        return;
+    };
+    writer.push(
+        "boot_history",
+        "previous_boots",
+        "Number of previous boots this boot is compared against",
+        MetricKind::Gauge,
+        labels,
+        boot_history.previous_boots.into(),
+    );
+    writer.push(
+        "boot_history",
+        "regressed_units",
+        "Number of units slower at boot than the regression threshold over their median",
+        MetricKind::Gauge,
+        labels,
+        boot_history.regressed_units.into(),
+    );
+    if let Some(total) = &boot_history.total {
+        writer.push_struct_fields("boot_history_total", "Boot history total", total, labels);
+    }
+    for (unit_name, comparison) in &boot_history.units {
+        writer.push_struct_fields(
+            "boot_history_unit",
+            "Boot history unit",
+            comparison,
+            &with_label(labels, "unit", unit_name),
+        );
+    }
+}
+
+fn add_verify_stats<W: MetricWriter>(
+    writer: &mut W,
+    optional_verify_stats: &Option<crate::verify::VerifyStats>,
+    labels: &Labels,
+) {
+    let Some(verify_stats) = optional_verify_stats else {
+        return;
+    };
+    writer.push(
+        "verify",
+        "failing_units",
+        "Number of units failing verification",
+        MetricKind::Gauge,
+        labels,
+        verify_stats.total.into(),
+    );
+    for (unit_type, count) in &verify_stats.by_type {
+        writer.push(
+            "verify",
+            "failing_units_by_type",
+            "Number of units failing verification by unit type",
+            MetricKind::Gauge,
+            &with_label(labels, "unit_type", unit_type),
+            (*count).into(),
+        );
+    }
+}
+
+fn add_jobs<W: MetricWriter>(
+    writer: &mut W,
+    optional_job_stats: &Option<crate::jobs::JobStats>,
+    labels: &Labels,
+) {
+    let Some(job_stats) = optional_job_stats else {
+        return;
+    };
+    writer.push(
+        "jobs",
+        "queued",
+        "Number of jobs in the systemd job queue",
+        MetricKind::Gauge,
+        labels,
+        job_stats.queued.into(),
+    );
+    writer.push(
+        "jobs",
+        "stuck",
+        "Number of queued jobs older than the stuck job threshold",
+        MetricKind::Gauge,
+        labels,
+        (job_stats.stuck_jobs.len() as u64).into(),
+    );
+    for (job_type, type_stats) in &job_stats.by_type {
+        let type_labels = with_label(labels, "job_type", job_type);
+        if let Ok(serde_json::Value::Object(map)) = serde_json::to_value(&type_stats.results) {
+            for (result, count) in map {
+                let Some(count) = MetricValue::from_json(&count) else {
+                    continue;
+                };
+                writer.push(
+                    "jobs",
+                    "finished",
+                    "Number of jobs finished since the previous run by job type and result",
+                    MetricKind::Gauge,
+                    &with_label(&type_labels, "result", &result),
+                    count,
+                );
+            }
+        }
+        // results is nested so only the durations are pushed here
+        writer.push_struct_fields("jobs", "Job", type_stats, &type_labels);
+    }
+    for stuck_job in &job_stats.stuck_jobs {
+        let mut stuck_labels = with_label(labels, "unit", &stuck_job.unit);
+        stuck_labels.push(("job_type", &stuck_job.job_type));
+        stuck_labels.push(("state", &stuck_job.state));
+        writer.push(
+            "jobs_stuck",
+            "age_seconds",
+            "Seconds a stuck job has been queued",
+            MetricKind::Gauge,
+            &stuck_labels,
+            stuck_job.age_secs.into(),
+        );
+    }
+}
+
+fn add_collector_timings<W: MetricWriter>(writer: &mut W, timings: &[crate::CollectorTiming]) {
+    for t in timings {
+        let labels = vec![("collector", t.name.as_str())];
+        writer.push(
+            "collector",
+            "start_offset_ms",
+            "Milliseconds from the top of the run until the collector was first polled",
+            MetricKind::Gauge,
+            &labels,
+            t.start_offset_ms.into(),
+        );
+        writer.push(
+            "collector",
+            "elapsed_ms",
+            "Milliseconds the collector took to complete",
+            MetricKind::Gauge,
+            &labels,
+            t.elapsed_ms.into(),
+        );
+        writer.push(
+            "collector",
+            "success",
+            "Whether the collector succeeded on the last run",
+            MetricKind::Gauge,
+            &labels,
+            t.success.into(),
+        );
+    }
+}
+
+/// Leaves of each custom collector's value labelled with the collector name.
+/// Fields are the flattened paths into the value, or `value` when it is a
+/// plain value.
+fn add_extra<W: MetricWriter>(
+    writer: &mut W,
+    extra: &BTreeMap<String, serde_json::Value>,
+    labels: &Labels,
+) {
+    for (collector_name, value) in extra {
+        let collector_labels = with_label(labels, "collector", collector_name);
+        let mut flat_values = BTreeMap::new();
+        crate::json::flatten_extra_value(collector_name.clone(), value, &mut flat_values);
+        let field_prefix = format!("{collector_name}.");
+        for (key, value) in flat_values {
+            let field_name = key.strip_prefix(&field_prefix).unwrap_or("value");
+            let metric_value = match value {
+                serde_json::Value::String(s) => Some(MetricValue::String(s)),
+                value => MetricValue::from_json(&value),
+            };
+            if let Some(metric_value) = metric_value {
+                writer.push(
+                    "extra",
+                    field_name,
+                    &format!("Custom collector {field_name} value"),
+                    MetricKind::Gauge,
+                    &collector_labels,
+                    metric_value,
+                );
+            }
+        }
+    }
+}
+
+/// Every section collected for the host or for a machine
+fn add_host<W: MetricWriter>(writer: &mut W, stats: HostStats, labels: &Labels) {
+    add_networkd(writer, stats.networkd, labels);
+    add_pid1(writer, stats.pid1, labels);
+    add_system(writer, stats.system_state, stats.version, labels);
+    add_manager(writer, stats.manager, labels);
+    add_manager_defaults(writer, stats.manager_defaults, labels);
+    add_tainted(writer, stats.tainted, labels);
+    add_watchdog(writer, stats.watchdog, labels);
+    add_oom(writer, stats.oom, labels);
+    add_pressure(writer, stats.pressure, labels);
+    add_slices(writer, stats.slices, labels);
+    add_units(writer, stats.units, labels);
+    add_dbus_stats(writer, stats.dbus_stats, labels);
+    add_boot_blame(writer, stats.boot_blame, labels);
+    add_boot_timeline(writer, stats.boot_timeline, labels);
+    add_boot_critical_chain(writer, stats.boot_critical_chain, labels);
+    add_boot_history(writer, stats.boot_history, labels);
+    add_verify_stats(writer, stats.verify_stats, labels);
+    add_jobs(writer, stats.jobs, labels);
+}
+
+/// Push every sample of `stats` to `writer`. Machine samples carry a
+/// `machine` label.
+pub(crate) fn walk<W: MetricWriter>(writer: &mut W, stats: &MonitordStats) {
+    let labels: Labels = Vec::new();
+
+    writer.push(
+        "stat_collection",
+        "run_time_ms",
+        "End-to-end duration of the last stat collection run in milliseconds",
+        MetricKind::Gauge,
+        &labels,
+        stats.stat_collection_run_time_ms.into(),
+    );
+    add_collector_timings(writer, &stats.collector_timings);
+    add_host(writer, HostStats::from(stats), &labels);
+    add_extra(writer, &stats.extra, &labels);
+    for (machine, machine_stats) in &stats.machines {
+        let machine_labels = vec![("machine", machine.as_str())];
+        add_host(writer, HostStats::from(machine_stats), &machine_labels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    /// Records the subsystems pushed with and without a machine label
+    #[derive(Default)]
+    struct Recorder {
+        host: BTreeSet<String>,
+        machine: BTreeSet<String>,
+    }
+
+    impl MetricWriter for Recorder {
+        fn push(
+            &mut self,
+            subsystem: &str,
+            _field: &str,
+            _help: &str,
+            _kind: MetricKind,
+            labels: &[(&str, &str)],
+            _value: MetricValue,
+        ) {
+            match labels.iter().any(|(k, _)| *k == "machine") {
+                true => self.machine.insert(subsystem.to_string()),
+                false => self.host.insert(subsystem.to_string()),
+            };
+        }
+
+        fn push_info(
+            &mut self,
+            subsystem: &str,
+            field: &str,
+            help: &str,
+            labels: &[(&str, &str)],
+            value: &str,
+        ) {
+            self.push(
+                subsystem,
+                field,
+                help,
+                MetricKind::Gauge,
+                labels,
+                MetricValue::String(value.to_string()),
+            );
+        }
+    }
+
+    fn return_machine_stats() -> MachineStats {
+        let mut stats = MachineStats {
+            pid1: Some(pid1::Pid1Stats::default()),
+            manager: Some(crate::manager::ManagerStats::default()),
+            manager_defaults: Some(crate::manager_defaults::ManagerDefaultsStats::default()),
+            tainted: Some(crate::tainted::TaintedStats::default()),
+            watchdog: Some(crate::watchdog::WatchdogStats::default()),
+            oom: Some(crate::oom::OomStats {
+                host_oom_kill: Some(1),
+                ..Default::default()
+            }),
+            boot_timeline: Some(crate::boot_timeline::BootTimelineStats::default()),
+            boot_history: Some(crate::boot_history::BootHistoryStats::default()),
+            verify_stats: Some(crate::verify::VerifyStats::default()),
+            jobs: Some(crate::jobs::JobStats::default()),
+            boot_blame: Some(crate::boot::BootBlameStats::from([(
+                String::from("foo.service"),
+                1.5,
+            )])),
+            ..Default::default()
+        };
+        stats
+            .units
+            .service_stats
+            .insert(String::from("foo.service"), units::ServiceStats::default());
+        stats
+    }
+
+    #[test]
+    fn test_walk_machines_match_host() {
+        let machine = return_machine_stats();
+        let mut stats = MonitordStats {
+            networkd: machine.networkd.clone(),
+            pid1: machine.pid1.clone(),
+            manager: machine.manager.clone(),
+            manager_defaults: machine.manager_defaults.clone(),
+            tainted: machine.tainted.clone(),
+            watchdog: machine.watchdog.clone(),
+            oom: machine.oom.clone(),
+            units: machine.units.clone(),
+            boot_timeline: machine.boot_timeline.clone(),
+            boot_history: machine.boot_history.clone(),
+            verify_stats: machine.verify_stats.clone(),
+            jobs: machine.jobs.clone(),
+            boot_blame: machine.boot_blame.clone(),
+            ..Default::default()
+        };
+        stats.machines.insert(String::from("foo"), machine);
+
+        let mut recorder = Recorder::default();
+        walk(&mut recorder, &stats);
+
+        for subsystem in ["watchdog", "oom", "boot_timeline", "boot_history", "jobs"] {
+            assert!(recorder.machine.contains(subsystem), "{subsystem} missing");
+        }
+        // Only the run wide sections are host only
+        let host_only: Vec<&String> = recorder.host.difference(&recorder.machine).collect();
+        assert_eq!(vec!["stat_collection"], host_only);
+    }
+}
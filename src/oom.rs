@@ -0,0 +1,267 @@
+//! # oom module
+//!
+//! Per-service OOM killer counts from the `memory.events` file of each
+//! service's cgroup, so services losing processes to the kernel OOM killer
+//! show up. In daemon mode the `*_delta` fields count the increase since the
+//! previous run. Only collected for the host, not for machines.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use tokio::sync::RwLock;
+use tracing::debug;
+use zbus::zvariant::ObjectPath;
+
+use crate::cgroup::cgroup_path;
+use crate::cgroup::get_control_group;
+use crate::cgroup::read_flat_keyed;
+use crate::cgroup::CGROUP_ROOT;
+use crate::config::Config;
+use crate::dbus::zbus_systemd::ManagerProxy;
+use crate::units::ListedUnit;
+use crate::units::SYSTEMD_SERVICE_SUFFIX;
+use crate::MachineStats;
+
+const MEMORY_EVENTS_FILE: &str = "memory.events";
+const VMSTAT_PATH: &str = "/proc/vmstat";
+
+/// `memory.events` counters of a service's cgroup. The cgroup is recreated
+/// when the service restarts, which resets the counters.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ServiceOomStats {
+    /// Times the cgroup was throttled for going over MemoryHigh=
+    pub high: u64,
+    /// Times the cgroup's usage was about to go over MemoryMax=
+    pub max: u64,
+    /// Times the cgroup hit its limit and the OOM killer was invoked
+    pub oom: u64,
+    /// Processes in the cgroup killed by the OOM killer
+    pub oom_kill: u64,
+    /// Times the whole cgroup was killed (OOMPolicy=kill / memory.oom.group)
+    pub oom_group_kill: u64,
+    /// Increase of `oom` since the previous run
+    pub oom_delta: u64,
+    /// Increase of `oom_kill` since the previous run
+    pub oom_kill_delta: u64,
+    /// Increase of `oom_group_kill` since the previous run
+    pub oom_group_kill_delta: u64,
+}
+
+impl ServiceOomStats {
+    fn new(memory_events: &HashMap<String, u64>, previous: Option<&ServiceOomStats>) -> Self {
+        let get = |key: &str| memory_events.get(key).copied().unwrap_or_default();
+        let mut stats = ServiceOomStats {
+            high: get("high"),
+            max: get("max"),
+            oom: get("oom"),
+            oom_kill: get("oom_kill"),
+            oom_group_kill: get("oom_group_kill"),
+            ..Default::default()
+        };
+        if let Some(previous) = previous {
+            stats.oom_delta = counter_delta(stats.oom, previous.oom);
+            stats.oom_kill_delta = counter_delta(stats.oom_kill, previous.oom_kill);
+            stats.oom_group_kill_delta =
+                counter_delta(stats.oom_group_kill, previous.oom_group_kill);
+        }
+        stats
+    }
+
+    fn add(&mut self, other: &ServiceOomStats) {
+        self.high += other.high;
+        self.max += other.max;
+        self.oom += other.oom;
+        self.oom_kill += other.oom_kill;
+        self.oom_group_kill += other.oom_group_kill;
+        self.oom_delta += other.oom_delta;
+        self.oom_kill_delta += other.oom_kill_delta;
+        self.oom_group_kill_delta += other.oom_group_kill_delta;
+    }
+}
+
+/// OOM counts for the tracked services
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct OomStats {
+    /// Service name to its cgroup's counters. Services without a cgroup
+    /// (not running) are left out.
+    pub services: HashMap<String, ServiceOomStats>,
+    /// Sum of the service counters
+    pub total: ServiceOomStats,
+    /// Every OOM kill on the host since boot, including processes outside
+    /// the tracked services (`oom_kill` in /proc/vmstat)
+    pub host_oom_kill: Option<u64>,
+}
+
+/// A counter that went backwards was reset, so everything since counts
+fn counter_delta(current: u64, previous: u64) -> u64 {
+    match current >= previous {
+        true => current - previous,
+        false => current,
+    }
+}
+
+/// `[services]` plus, with `all_services`, every service that passes the
+/// `[units]` state_stats allowlist and blocklist
+fn tracked_services<'a>(config: &Config, units: &'a [ListedUnit]) -> Vec<&'a ListedUnit> {
+    units
+        .iter()
+        .filter(|unit| {
+            config.services.contains(&unit.name)
+                || (config.oom.all_services
+                    && unit.name.ends_with(SYSTEMD_SERVICE_SUFFIX)
+                    && crate::units::is_state_tracked(&config.units, &unit.name))
+        })
+        .collect()
+}
+
+pub async fn get_oom_stats(
+    config: &Config,
+    connection: &zbus::Connection,
+    previous: Option<&OomStats>,
+) -> Result<OomStats> {
+    let manager = ManagerProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let units: Vec<ListedUnit> = manager
+        .list_units()
+        .await?
+        .into_iter()
+        .map(ListedUnit::from)
+        .collect();
+
+    let cgroup_root = Path::new(CGROUP_ROOT);
+    let mut stats = OomStats::default();
+    for unit in tracked_services(config, &units) {
+        let control_group =
+            match get_control_group(connection, ObjectPath::from(&unit.unit_object_path)).await {
+                Ok(control_group) if !control_group.is_empty() => control_group,
+                Ok(_) => continue,
+                Err(err) => {
+                    debug!("Unable to get ControlGroup of {}: {}", unit.name, err);
+                    continue;
+                }
+            };
+        let events_path = cgroup_path(cgroup_root, &control_group).join(MEMORY_EVENTS_FILE);
+        let memory_events = match read_flat_keyed(&events_path).await {
+            Ok(memory_events) => memory_events,
+            // The service stopped since we listed it, or no memory controller
+            Err(err) => {
+                debug!("Unable to read {}: {}", events_path.display(), err);
+                continue;
+            }
+        };
+        let previous_service = previous.and_then(|p| p.services.get(&unit.name));
+        let service_stats = ServiceOomStats::new(&memory_events, previous_service);
+        stats.total.add(&service_stats);
+        stats.services.insert(unit.name.clone(), service_stats);
+    }
+
+    match read_flat_keyed(Path::new(VMSTAT_PATH)).await {
+        Ok(vmstat) => stats.host_oom_kill = vmstat.get("oom_kill").copied(),
+        Err(err) => debug!("Unable to read {}: {}", VMSTAT_PATH, err),
+    }
+    Ok(stats)
+}
+
+/// Async wrapper than can update OOM stats when passed a locked struct.
+/// The previous run's stats are used for the deltas.
+pub async fn update_oom_stats(
+    config: Arc<Config>,
+    connection: zbus::Connection,
+    locked_machine_stats: Arc<RwLock<MachineStats>>,
+) -> Result<()> {
+    let previous = locked_machine_stats.read().await.oom.clone();
+    let oom_stats = get_oom_stats(&config, &connection, previous.as_ref())
+        .await
+        .map_err(|e| anyhow::anyhow!("Error getting OOM stats: {:?}", e))?;
+    if oom_stats.total.oom_kill_delta > 0 {
+        debug!(
+            "{} processes OOM killed in tracked services since the previous run",
+            oom_stats.total.oom_kill_delta
+        );
+    }
+    let mut machine_stats = locked_machine_stats.write().await;
+    machine_stats.oom = Some(oom_stats);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn listed_unit(name: &str) -> ListedUnit {
+        ListedUnit {
+            name: name.to_string(),
+            description: String::new(),
+            load_state: String::from("loaded"),
+            active_state: String::from("active"),
+            sub_state: String::from("running"),
+            follow_unit: String::new(),
+            unit_object_path: ObjectPath::try_from("/org/freedesktop/systemd1/unit/foo")
+                .expect("valid path")
+                .into(),
+            job_id: 0,
+            job_type: String::new(),
+            job_object_path: ObjectPath::try_from("/").expect("valid path").into(),
+        }
+    }
+
+    #[test]
+    fn test_service_oom_stats() {
+        let memory_events = HashMap::from([
+            (String::from("high"), 7),
+            (String::from("oom"), 3),
+            (String::from("oom_kill"), 2),
+        ]);
+        let first = ServiceOomStats::new(&memory_events, None);
+        assert_eq!(
+            ServiceOomStats {
+                high: 7,
+                oom: 3,
+                oom_kill: 2,
+                ..Default::default()
+            },
+            first
+        );
+
+        let memory_events =
+            HashMap::from([(String::from("oom"), 5), (String::from("oom_kill"), 1)]);
+        let second = ServiceOomStats::new(&memory_events, Some(&first));
+        assert_eq!(2, second.oom_delta);
+        // The service restarted into a new cgroup
+        assert_eq!(1, second.oom_kill_delta);
+        assert_eq!(0, second.oom_group_kill_delta);
+    }
+
+    #[test]
+    fn test_tracked_services() {
+        let units = vec![
+            listed_unit("foo.service"),
+            listed_unit("bar.service"),
+            listed_unit("baz.socket"),
+        ];
+        let mut config = Config {
+            services: HashSet::from([String::from("foo.service")]),
+            ..Default::default()
+        };
+        let names = |config: &Config| -> Vec<String> {
+            tracked_services(config, &units)
+                .iter()
+                .map(|u| u.name.clone())
+                .collect()
+        };
+        assert_eq!(vec![String::from("foo.service")], names(&config));
+
+        config.oom.all_services = true;
+        config.units.state_stats_blocklist = HashSet::from([String::from("foo.service")]);
+        // [services] are always tracked
+        assert_eq!(
+            vec![String::from("foo.service"), String::from("bar.service")],
+            names(&config)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! # pressure module
+//!
+//! Pressure stall information (PSI) of the top level slices, to see whether
+//! system services, user sessions or containers are stalled on CPU, memory
+//! or IO. Per-service PSI is collected with the service stats in `units`.
+//! Slices the `[slices]` collector reports (with their PSI) are skipped so
+//! they aren't emitted twice.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::cgroup::read_cgroup_pressure;
+use crate::cgroup::slice_cgroup_path;
+use crate::cgroup::CgroupPressure;
+use crate::cgroup::CGROUP_ROOT;
+use crate::config::Config;
+use crate::config::SlicesConfig;
+use crate::slices::is_slice_tracked;
+use crate::MachineStats;
+
+/// Slices PSI is collected for
+pub const PRESSURE_SLICES: &[&str] = &["machine.slice", "system.slice", "user.slice"];
+
+/// PSI per slice
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PressureStats {
+    /// Slice name to its PSI. Slices without a cgroup (e.g. machine.slice
+    /// without containers) are left out.
+    pub slices: HashMap<String, CgroupPressure>,
+}
+
+/// `fs_root` is prepended to the cgroup mount — empty for the host,
+/// `/proc/<leader>/root` for machines
+pub async fn get_pressure_stats(slices_config: &SlicesConfig, fs_root: &str) -> PressureStats {
+    let cgroup_root = format!("{fs_root}{CGROUP_ROOT}");
+    let mut stats = PressureStats::default();
+    for slice in PRESSURE_SLICES {
+        // Reported as slices.<slice>.pressure_*
+        if slices_config.enabled && is_slice_tracked(slices_config, slice) {
+            continue;
+        }
+        let pressure =
+            read_cgroup_pressure(&slice_cgroup_path(Path::new(&cgroup_root), slice)).await;
+        if pressure.lines().is_empty() {
+            continue;
+        }
+        stats.slices.insert(slice.to_string(), pressure);
+    }
+    stats
+}
+
+/// Async wrapper than can update slice pressure stats when passed a locked struct
+pub async fn update_pressure_stats(
+    config: Arc<Config>,
+    fs_root: String,
+    locked_machine_stats: Arc<RwLock<MachineStats>>,
+) -> anyhow::Result<()> {
+    let pressure_stats = get_pressure_stats(&config.slices, &fs_root).await;
+    let mut machine_stats = locked_machine_stats.write().await;
+    machine_stats.pressure = Some(pressure_stats);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_pressure_stats() {
+        let temp_dir = tempfile::tempdir().expect("create temp dir");
+        let fs_root = temp_dir.path().to_str().expect("utf-8 temp dir");
+        let system_slice = format!("{fs_root}{CGROUP_ROOT}/system.slice");
+        tokio::fs::create_dir_all(&system_slice)
+            .await
+            .expect("create system.slice");
+        tokio::fs::write(
+            format!("{system_slice}/io.pressure"),
+            "some avg10=3.00 avg60=2.00 avg300=1.00 total=100\nfull avg10=1.00 avg60=0.50 avg300=0.25 total=50\n",
+        )
+        .await
+        .expect("write io.pressure");
+
+        let mut slices_config = SlicesConfig::default();
+        let stats = get_pressure_stats(&slices_config, fs_root).await;
+        assert_eq!(
+            vec!["system.slice"],
+            stats.slices.keys().collect::<Vec<_>>()
+        );
+        let io = stats.slices["system.slice"]
+            .io
+            .clone()
+            .expect("io pressure");
+        assert_eq!(3.0, io.some.avg10);
+        assert_eq!(50, io.full.total_usec);
+
+        // The slice collector reports it instead
+        slices_config.enabled = true;
+        assert!(get_pressure_stats(&slices_config, fs_root)
+            .await
+            .slices
+            .is_empty());
+        // unless it is blocked there
+        slices_config.blocklist.insert(String::from("system.slice"));
+        assert_eq!(
+            1,
+            get_pressure_stats(&slices_config, fs_root)
+                .await
+                .slices
+                .len()
+        );
+    }
+}
@@ -0,0 +1,463 @@
+//! # prometheus module
+//!
+//! Render `MonitordStats` in the Prometheus text exposition format and serve it
+//! over HTTP on `/metrics` when monitord is running in daemon mode.
+//! Ref: <https://prometheus.io/docs/instrumenting/exposition_formats/>
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::RwLock;
+use tracing::debug;
+use tracing::error;
+
+use crate::metrics::with_label;
+use crate::metrics::MetricKind;
+use crate::metrics::MetricValue;
+use crate::metrics::MetricWriter;
+use crate::MonitordStats;
+
+#[derive(Error, Debug)]
+pub enum MonitordPrometheusError {
+    #[error("Prometheus listener I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Timed out reading HTTP request")]
+    RequestTimeout,
+    #[error("Malformed HTTP request")]
+    MalformedRequest,
+}
+
+/// Every metric name is prefixed with this namespace
+const METRIC_NAMESPACE: &str = "monitord";
+/// Content type for version 0.0.4 of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+/// Path metrics are served on
+pub const METRICS_PATH: &str = "/metrics";
+/// Largest HTTP request head we are willing to buffer
+const MAX_REQUEST_BYTES: usize = 8192;
+/// How long a client gets to send us its request head
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Pause after a failed accept (e.g. EMFILE) so we don't spin on the error
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(500);
+
+/// All samples sharing one metric name, rendered under a single HELP/TYPE pair
+struct MetricFamily {
+    help: String,
+    kind: MetricKind,
+    samples: Vec<(Vec<(String, String)>, f64)>,
+}
+
+/// Metric families keyed (and therefore rendered sorted) by full metric name
+#[derive(Default)]
+struct MetricSet {
+    families: BTreeMap<String, MetricFamily>,
+}
+
+impl MetricSet {
+    fn push_sample(
+        &mut self,
+        name: &str,
+        help: &str,
+        kind: MetricKind,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) {
+        let mut full_name = sanitize_metric_name(&format!("{METRIC_NAMESPACE}_{name}"));
+        // Counters get the conventional `_total` suffix
+        if kind == MetricKind::Counter && !full_name.ends_with("_total") {
+            full_name.push_str("_total");
+        }
+        let family = self
+            .families
+            .entry(full_name)
+            .or_insert_with(|| MetricFamily {
+                help: help.to_string(),
+                kind,
+                samples: Vec::new(),
+            });
+        family.samples.push((
+            labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            value,
+        ));
+    }
+
+    fn render(&self) -> String {
+        let mut output = String::new();
+        for (name, family) in &self.families {
+            // Writing to a String can not fail
+            let _ = writeln!(output, "# HELP {} {}", name, escape_help(&family.help));
+            let _ = writeln!(output, "# TYPE {} {}", name, metric_type(family.kind));
+            for (labels, value) in &family.samples {
+                output.push_str(name);
+                if !labels.is_empty() {
+                    let rendered_labels: Vec<String> = labels
+                        .iter()
+                        .map(|(k, v)| format!("{}=\"{}\"", k, escape_label_value(v)))
+                        .collect();
+                    let _ = write!(output, "{{{}}}", rendered_labels.join(","));
+                }
+                let _ = writeln!(output, " {}", format_value(*value));
+            }
+        }
+        output
+    }
+}
+
+impl MetricWriter for MetricSet {
+    /// Samples are named `<subsystem>_<field>`. Strings can't be samples so
+    /// are skipped.
+    fn push(
+        &mut self,
+        subsystem: &str,
+        field: &str,
+        help: &str,
+        kind: MetricKind,
+        labels: &[(&str, &str)],
+        value: MetricValue,
+    ) {
+        if let Some(value) = value.as_f64() {
+            self.push_sample(&format!("{subsystem}_{field}"), help, kind, labels, value);
+        }
+    }
+
+    /// Strings become the `field` label of a `<subsystem>_info` gauge that is always 1
+    fn push_info(
+        &mut self,
+        subsystem: &str,
+        field: &str,
+        help: &str,
+        labels: &[(&str, &str)],
+        value: &str,
+    ) {
+        self.push_sample(
+            &format!("{subsystem}_info"),
+            &format!("{help}, always 1"),
+            MetricKind::Gauge,
+            &with_label(labels, field, value),
+            1.0,
+        );
+    }
+}
+
+fn metric_type(kind: MetricKind) -> &'static str {
+    match kind {
+        MetricKind::Counter => "counter",
+        MetricKind::Gauge => "gauge",
+    }
+}
+
+/// Metric names may only contain `[a-zA-Z0-9_:]`
+fn sanitize_metric_name(name: &str) -> String {
+    name.chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect()
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', r"\\").replace('\n', r"\n")
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        match value.is_sign_positive() {
+            true => "+Inf".to_string(),
+            false => "-Inf".to_string(),
+        }
+    } else {
+        value.to_string()
+    }
+}
+
+fn build_metric_set(stats: &MonitordStats) -> MetricSet {
+    let mut set = MetricSet::default();
+    crate::metrics::walk(&mut set, stats);
+    set
+}
+
+/// Render all stats in the Prometheus text exposition format
+pub fn render(stats: &MonitordStats) -> String {
+    build_metric_set(stats).render()
+}
+
+/// Render all stats plus the time of the run that produced them. Used for
+/// node_exporter textfile output where the file can go stale between runs.
+pub fn render_with_run_timestamp(stats: &MonitordStats, last_run_timestamp_secs: f64) -> String {
+    let mut set = build_metric_set(stats);
+    set.push_sample(
+        "last_run_timestamp_seconds",
+        "Unix time the last stat collection run completed",
+        MetricKind::Gauge,
+        &[],
+        last_run_timestamp_secs,
+    );
+    set.render()
+}
+
+/// Bind the metrics listener. Done up front so a bad address fails startup.
+pub async fn bind(listen_address: SocketAddr) -> Result<TcpListener, MonitordPrometheusError> {
+    Ok(TcpListener::bind(listen_address).await?)
+}
+
+/// Accept connections forever, answering `/metrics` scrapes with the latest stats
+pub async fn serve_metrics(listener: TcpListener, locked_stats: Arc<RwLock<MonitordStats>>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let stats_clone = locked_stats.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = handle_connection(stream, stats_clone).await {
+                        debug!("Prometheus request from {} failed: {:?}", peer, err);
+                    }
+                });
+            }
+            Err(err) => {
+                error!("Unable to accept Prometheus connection: {:?}", err);
+                tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+            }
+        }
+    }
+}
+
+/// Read until the end of the HTTP request head. We never need a request body.
+async fn read_request_head(stream: &mut TcpStream) -> Result<String, MonitordPrometheusError> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    loop {
+        let read_bytes = stream.read(&mut buf).await?;
+        if read_bytes == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read_bytes]);
+        if request.windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+        if request.len() > MAX_REQUEST_BYTES {
+            return Err(MonitordPrometheusError::MalformedRequest);
+        }
+    }
+    Ok(String::from_utf8_lossy(&request).into_owned())
+}
+
+fn http_response(status: &str, content_type: &str, body: &str, include_body: bool) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    if include_body {
+        response.push_str(body);
+    }
+    response.into_bytes()
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    locked_stats: Arc<RwLock<MonitordStats>>,
+) -> Result<(), MonitordPrometheusError> {
+    let request = tokio::time::timeout(REQUEST_READ_TIMEOUT, read_request_head(&mut stream))
+        .await
+        .map_err(|_| MonitordPrometheusError::RequestTimeout)??;
+    let mut request_line = request
+        .lines()
+        .next()
+        .ok_or(MonitordPrometheusError::MalformedRequest)?
+        .split_whitespace();
+    let method = request_line
+        .next()
+        .ok_or(MonitordPrometheusError::MalformedRequest)?;
+    let target = request_line
+        .next()
+        .ok_or(MonitordPrometheusError::MalformedRequest)?;
+    let path = target.split('?').next().unwrap_or(target);
+
+    let response = match (method, path) {
+        ("GET" | "HEAD", METRICS_PATH) => {
+            let body = render(&*locked_stats.read().await);
+            http_response("200 OK", CONTENT_TYPE, &body, method == "GET")
+        }
+        ("GET" | "HEAD", _) => http_response(
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not Found\n",
+            method == "GET",
+        ),
+        _ => http_response(
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Method Not Allowed\n",
+            true,
+        ),
+    };
+    stream.write_all(&response).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networkd;
+    use crate::units;
+
+    fn return_monitord_stats() -> MonitordStats {
+        let mut stats = MonitordStats {
+            system_state: crate::system::SystemdSystemState::degraded,
+            version: String::from("255.7-1.fc40")
+                .try_into()
+                .expect("Unable to make SystemdVersion struct"),
+            stat_collection_run_time_ms: 69.0,
+            ..Default::default()
+        };
+        stats.networkd.managed_interfaces = 1;
+        stats
+            .networkd
+            .interfaces_state
+            .push(networkd::InterfaceState {
+                name: "eth0".to_string(),
+                oper_state: networkd::OperState::routable,
+                ..Default::default()
+            });
+        stats.units.service_stats.insert(
+            String::from("unittest.service"),
+            units::ServiceStats {
+                nrestarts: 3,
+                memory_current: 1024,
+                ..Default::default()
+            },
+        );
+        let mut machine = crate::MachineStats::default();
+        machine.units.failed_units = 2;
+        machine.units.unit_states.insert(
+            String::from("weird\"name.service"),
+            units::UnitStates {
+                unhealthy: true,
+                ..Default::default()
+            },
+        );
+        stats.machines.insert(String::from("foo"), machine);
+        stats.extra.insert(
+            String::from("custom"),
+            serde_json::json!({"count": 5, "ok": true, "name": "bar"}),
+        );
+        stats
+    }
+
+    #[test]
+    fn test_render() {
+        let output = render(&return_monitord_stats());
+        assert!(output.contains("# TYPE monitord_system_state gauge\nmonitord_system_state 4\n"));
+        assert!(output.contains("monitord_system_info{version=\"255.7-1.fc40\"} 1\n"));
+        assert!(output.contains("monitord_networkd_oper_state{interface=\"eth0\"} 9\n"));
+        assert!(output.contains("# TYPE monitord_service_nrestarts_total counter\n"));
+        assert!(output.contains("monitord_service_nrestarts_total{unit=\"unittest.service\"} 3\n"));
+        assert!(
+            output.contains("monitord_service_memory_current{unit=\"unittest.service\"} 1024\n")
+        );
+        assert!(output.contains("monitord_units_failed_units{machine=\"foo\"} 2\n"));
+        assert!(output.contains(
+            "monitord_unit_unhealthy{machine=\"foo\",unit=\"weird\\\"name.service\"} 1\n"
+        ));
+        assert!(output.contains("monitord_stat_collection_run_time_ms 69\n"));
+        assert!(output.contains("# TYPE monitord_extra_count gauge\n"));
+        assert!(output.contains("monitord_extra_count{collector=\"custom\"} 5\n"));
+        assert!(output.contains("monitord_extra_ok{collector=\"custom\"} 1\n"));
+        assert!(!output.contains("monitord_extra_name"));
+    }
+
+    #[test]
+    fn test_render_help_and_type_once_per_family() {
+        let output = render(&return_monitord_stats());
+        // Host and machine foo both emit units_failed_units
+        assert_eq!(
+            1,
+            output
+                .matches("# HELP monitord_units_failed_units ")
+                .count()
+        );
+        assert_eq!(
+            1,
+            output
+                .matches("# TYPE monitord_units_failed_units gauge")
+                .count()
+        );
+        assert_eq!(2, output.matches("\nmonitord_units_failed_units").count());
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(r#"a\\b\"c\nd"#, escape_label_value("a\\b\"c\nd"));
+        assert_eq!(r"a\\b\nc", escape_help("a\\b\nc"));
+        assert_eq!(
+            "monitord_foo_bar_baz",
+            sanitize_metric_name("monitord_foo-bar.baz")
+        );
+        assert_eq!("NaN", format_value(f64::NAN));
+        assert_eq!("+Inf", format_value(f64::INFINITY));
+        assert_eq!("0.5", format_value(0.5));
+    }
+
+    async fn http_get(address: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address)
+            .await
+            .expect("Unable to connect to metrics listener");
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .expect("Unable to send request");
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .await
+            .expect("Unable to read response");
+        response
+    }
+
+    #[tokio::test]
+    async fn test_serve_metrics() {
+        let listener = bind("127.0.0.1:0".parse().expect("Invalid address"))
+            .await
+            .expect("Unable to bind");
+        let address = listener.local_addr().expect("No local address");
+        let locked_stats = Arc::new(RwLock::new(return_monitord_stats()));
+        let server = tokio::spawn(serve_metrics(listener, locked_stats));
+
+        let response = http_get(address, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(CONTENT_TYPE));
+        assert!(response.contains("monitord_system_state 4\n"));
+
+        let response = http_get(address, "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        let response = http_get(address, "POST /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+
+        server.abort();
+    }
+}
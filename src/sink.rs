@@ -0,0 +1,143 @@
+//! # sink module
+//!
+//! Where stats go after every collection run. Each enabled output (stdout,
+//! StatsD, Graphite, node_exporter textfile, the Varlink and D-Bus services)
+//! is a [`Sink`] and all of them are emitted to after each run. Library users
+//! can pass their own sinks in [`crate::StatCollectorOptions::sinks`].
+
+use std::path::PathBuf;
+
+use futures_util::future::BoxFuture;
+
+use crate::config::MonitordOutputFormat;
+use crate::MonitordStats;
+
+/// An output that receives the stats from every collection run.
+///
+/// A failing sink is logged and does not stop other sinks or the daemon.
+pub trait Sink: Send {
+    /// Name used when logging failures
+    fn name(&self) -> &str;
+
+    /// Send or write one run's stats
+    fn emit<'a>(&'a mut self, stats: &'a MonitordStats) -> BoxFuture<'a, anyhow::Result<()>>;
+}
+
+/// Print stats to stdout in the configured output format
+pub struct StdoutSink {
+    key_prefix: String,
+    output_format: MonitordOutputFormat,
+}
+
+impl StdoutSink {
+    pub fn new(key_prefix: &str, output_format: MonitordOutputFormat) -> Self {
+        Self {
+            key_prefix: key_prefix.to_string(),
+            output_format,
+        }
+    }
+}
+
+impl Sink for StdoutSink {
+    fn name(&self) -> &str {
+        "stdout"
+    }
+
+    fn emit<'a>(&'a mut self, stats: &'a MonitordStats) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            crate::print_stats(&self.key_prefix, &self.output_format, stats);
+            Ok(())
+        })
+    }
+}
+
+/// Atomically write Prometheus format stats for node_exporter's textfile collector
+pub struct TextfileSink {
+    path: PathBuf,
+}
+
+impl TextfileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl Sink for TextfileSink {
+    fn name(&self) -> &str {
+        "textfile"
+    }
+
+    fn emit<'a>(&'a mut self, stats: &'a MonitordStats) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            crate::textfile::write_textfile(&self.path, stats).await?;
+            Ok(())
+        })
+    }
+}
+
+impl Sink for crate::statsd::StatsdSink {
+    fn name(&self) -> &str {
+        "statsd"
+    }
+
+    fn emit<'a>(&'a mut self, stats: &'a MonitordStats) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move { Ok(self.send(stats).await?) })
+    }
+}
+
+impl Sink for crate::graphite::GraphiteSink {
+    fn name(&self) -> &str {
+        "graphite"
+    }
+
+    fn emit<'a>(&'a mut self, stats: &'a MonitordStats) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move { Ok(self.send(stats).await?) })
+    }
+}
+
+impl Sink for crate::varlink_server::StatsPublisher {
+    fn name(&self) -> &str {
+        "varlink-server"
+    }
+
+    fn emit<'a>(&'a mut self, stats: &'a MonitordStats) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.publish(stats).await;
+            Ok(())
+        })
+    }
+}
+
+impl Sink for crate::dbus_service::DbusServicePublisher {
+    fn name(&self) -> &str {
+        "dbus-service"
+    }
+
+    fn emit<'a>(&'a mut self, stats: &'a MonitordStats) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move { Ok(self.publish(stats).await?) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_textfile_sink() {
+        let dir = tempfile::tempdir().expect("Unable to make temp dir");
+        let path = dir.path().join("monitord.prom");
+        let mut sinks: Vec<Box<dyn Sink>> = vec![
+            Box::new(StdoutSink::new("monitord", MonitordOutputFormat::JsonFlat)),
+            Box::new(TextfileSink::new(path.clone())),
+        ];
+        let stats = MonitordStats::default();
+        for sink in sinks.iter_mut() {
+            sink.emit(&stats).await.expect("Sink failed");
+        }
+        assert_eq!(
+            vec!["stdout", "textfile"],
+            sinks.iter().map(|s| s.name()).collect::<Vec<_>>()
+        );
+        assert!(path.exists());
+    }
+}
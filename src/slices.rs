@@ -0,0 +1,131 @@
+//! # slices module
+//!
+//! Resource usage of every loaded `.slice` unit read from its cgroup: CPU,
+//! memory, tasks, IO and pressure stall information. Lets `system.slice`,
+//! `user.slice` and `machine.slice` (or any nested slice) consumption be
+//! compared, where `units` only counts slices.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::cgroup::read_cgroup_pressure;
+use crate::cgroup::read_cgroup_stats;
+use crate::cgroup::slice_cgroup_path;
+use crate::cgroup::CgroupPressure;
+use crate::cgroup::CgroupStats;
+use crate::cgroup::CGROUP_ROOT;
+use crate::config::SlicesConfig;
+use crate::dbus::zbus_systemd::ManagerProxy;
+use crate::MachineStats;
+
+pub const SYSTEMD_SLICE_SUFFIX: &str = ".slice";
+
+/// One slice's cgroup usage and PSI
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SliceStats {
+    #[serde(flatten)]
+    pub usage: CgroupStats,
+    pub pressure: CgroupPressure,
+}
+
+/// Stats of the tracked slices
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SlicesStats {
+    /// Slice name to its stats
+    pub slices: HashMap<String, SliceStats>,
+}
+
+/// Whether `slice` passes the `[slices]` allowlist and blocklist
+pub(crate) fn is_slice_tracked(config: &SlicesConfig, slice: &str) -> bool {
+    if config.blocklist.contains(slice) {
+        debug!("Skipping slice stats for {} due to blocklist", slice);
+        return false;
+    }
+    config.allowlist.is_empty() || config.allowlist.contains(slice)
+}
+
+/// `fs_root` is prepended to the cgroup mount — empty for the host,
+/// `/proc/<leader>/root` for machines
+pub async fn get_slices_stats(
+    config: &SlicesConfig,
+    connection: &zbus::Connection,
+    fs_root: &str,
+) -> anyhow::Result<SlicesStats> {
+    let manager = ManagerProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let slice_pattern = format!("*{SYSTEMD_SLICE_SUFFIX}");
+    let units = manager
+        .list_units_by_patterns(&[], &[slice_pattern.as_str()])
+        .await?;
+
+    let cgroup_root = format!("{fs_root}{CGROUP_ROOT}");
+    let mut stats = SlicesStats::default();
+    for (slice, ..) in units {
+        if !is_slice_tracked(config, &slice) {
+            continue;
+        }
+        let cgroup_dir = slice_cgroup_path(Path::new(&cgroup_root), &slice);
+        let (usage, pressure) = tokio::join!(
+            read_cgroup_stats(&cgroup_dir),
+            read_cgroup_pressure(&cgroup_dir)
+        );
+        stats.slices.insert(slice, SliceStats { usage, pressure });
+    }
+    Ok(stats)
+}
+
+/// Async wrapper than can update slice stats when passed a locked struct
+pub async fn update_slices_stats(
+    config: Arc<crate::config::Config>,
+    connection: zbus::Connection,
+    fs_root: String,
+    locked_machine_stats: Arc<RwLock<MachineStats>>,
+) -> anyhow::Result<()> {
+    let slices_stats = get_slices_stats(&config.slices, &connection, &fs_root)
+        .await
+        .map_err(|e| anyhow::anyhow!("Error getting slice stats: {:?}", e))?;
+    let mut machine_stats = locked_machine_stats.write().await;
+    machine_stats.slices = Some(slices_stats);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_is_slice_tracked() {
+        let mut config = SlicesConfig::default();
+        assert!(is_slice_tracked(&config, "system.slice"));
+
+        config.allowlist = HashSet::from([String::from("system.slice")]);
+        config.blocklist = HashSet::from([String::from("system.slice")]);
+        // blocklist wins
+        assert!(!is_slice_tracked(&config, "system.slice"));
+        assert!(!is_slice_tracked(&config, "user.slice"));
+
+        config.blocklist.clear();
+        assert!(is_slice_tracked(&config, "system.slice"));
+    }
+
+    #[test]
+    fn test_slice_stats_serialize_flat() {
+        let stats = SliceStats {
+            usage: CgroupStats {
+                memory_current: Some(4096),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&stats).expect("serialize slice stats");
+        assert_eq!(Some(&serde_json::json!(4096)), value.get("memory_current"));
+        assert!(value.get("pressure").is_some());
+    }
+}
@@ -0,0 +1,424 @@
+//! # statsd module
+//!
+//! Push each collection run's values to a StatsD (or DogStatsD) agent over UDP.
+//! Built on the same key/value pairs as `json::flatten`. Monotonic fields are
+//! sent as counters (the increase since the previous run), everything else as
+//! gauges.
+//! Ref: <https://github.com/statsd/statsd/blob/master/docs/metric_types.md>
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use thiserror::Error;
+use tokio::net::UdpSocket;
+use tracing::debug;
+
+use crate::config::StatsdConfig;
+use crate::MonitordStats;
+
+#[derive(Error, Debug)]
+pub enum MonitordStatsdError {
+    #[error("StatsD I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Unable to resolve StatsD address '{0}'")]
+    AddressResolution(String),
+}
+
+/// Flat key sections that carry an object name. The name is every segment
+/// between the section and the final field segment (unit names contain dots),
+/// or everything after the section when there is no field segment.
+/// (section, tag name, has trailing field segment)
+const TAGGED_SECTIONS: &[(&str, &str, bool)] = &[
+    ("services", "unit", true),
+    ("timers", "unit", true),
+    ("unit_states", "unit", true),
+    ("unit_state_transitions", "unit", true),
+    ("networkd", "interface", true),
+    ("boot.blame", "unit", false),
+    ("boot.critical_chain", "unit", true),
+    ("boot.history.units", "unit", true),
+    ("oom.services", "unit", true),
+    ("pressure", "slice", true),
+    ("slices", "slice", true),
+    ("dbus.peer", "peer", true),
+    ("dbus.cgroup", "cgroup", true),
+    ("dbus.user", "user", true),
+    ("jobs.types", "job_type", true),
+    ("jobs.stuck_jobs", "unit", true),
+    ("collector_timings", "collector", true),
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum MetricType {
+    Counter,
+    Gauge,
+}
+
+/// A single StatsD metric ready to be formatted
+#[derive(Clone, Debug, PartialEq)]
+struct Metric {
+    name: String,
+    value: f64,
+    metric_type: MetricType,
+    tags: Vec<(String, String)>,
+}
+
+impl Metric {
+    fn render(&self) -> Vec<String> {
+        let tags = match self.tags.is_empty() {
+            true => String::new(),
+            false => {
+                let tags: Vec<String> = self
+                    .tags
+                    .iter()
+                    .map(|(k, v)| format!("{}:{}", sanitize_tag(k), sanitize_tag(v)))
+                    .collect();
+                format!("|#{}", tags.join(","))
+            }
+        };
+        let name = sanitize_name(&self.name);
+        match self.metric_type {
+            MetricType::Counter => vec![format!("{}:{}|c{}", name, self.value, tags)],
+            // A signed gauge is a relative change in StatsD, so zero it first
+            MetricType::Gauge if self.value < 0.0 => vec![
+                format!("{}:0|g{}", name, tags),
+                format!("{}:{}|g{}", name, self.value, tags),
+            ],
+            MetricType::Gauge => vec![format!("{}:{}|g{}", name, self.value, tags)],
+        }
+    }
+}
+
+/// `:` and `|` delimit the value and type so can not appear in names
+fn sanitize_name(name: &str) -> String {
+    name.replace([':', '|', '\n'], "_")
+}
+
+/// DogStatsD tags are `,` separated and can not contain `|`
+fn sanitize_tag(tag: &str) -> String {
+    tag.replace([',', '|', '#', '\n'], "_")
+}
+
+/// Split a flat key (with key_prefix already removed) into a metric name and tags.
+/// Known machine names are needed as they may themselves contain dots.
+fn split_tags(key: &str, machine_names: &[&str]) -> (String, Vec<(String, String)>) {
+    let mut tags = Vec::new();
+    let mut rest = key;
+    let mut name_prefix = String::new();
+
+    if let Some(after_machines) = key.strip_prefix("machines.") {
+        if let Some(machine) = machine_names
+            .iter()
+            .find(|m| after_machines.starts_with(&format!("{m}.")))
+        {
+            tags.push(("machine".to_string(), machine.to_string()));
+            rest = &after_machines[machine.len() + 1..];
+            name_prefix.push_str("machines.");
+        }
+    }
+
+    for (section, tag_name, has_field) in TAGGED_SECTIONS {
+        let Some(object_and_field) = rest.strip_prefix(&format!("{section}.")) else {
+            continue;
+        };
+        let (object, field) = match has_field {
+            true => match object_and_field.rsplit_once('.') {
+                Some((object, field)) => (object, Some(field)),
+                // e.g. networkd.managed_interfaces has no object
+                None => continue,
+            },
+            false => (object_and_field, None),
+        };
+        tags.push((tag_name.to_string(), object.to_string()));
+        let name = match field {
+            Some(field) => format!("{name_prefix}{section}.{field}"),
+            None => format!("{name_prefix}{section}"),
+        };
+        return (name, tags);
+    }
+
+    (format!("{name_prefix}{rest}"), tags)
+}
+
+/// Sends one run's worth of metrics per `send` call, remembering counter values
+/// so the next run can send the increase
+pub struct StatsdSink {
+    socket: UdpSocket,
+    key_prefix: String,
+    dogstatsd_tags: bool,
+    max_packet_bytes: usize,
+    previous_counters: HashMap<String, f64>,
+}
+
+impl StatsdSink {
+    pub async fn new(config: &StatsdConfig, key_prefix: &str) -> Result<Self, MonitordStatsdError> {
+        let address = tokio::net::lookup_host(&config.address)
+            .await?
+            .next()
+            .ok_or_else(|| MonitordStatsdError::AddressResolution(config.address.clone()))?;
+        let bind_address = match address.is_ipv4() {
+            true => "0.0.0.0:0",
+            false => "[::]:0",
+        };
+        let socket = UdpSocket::bind(bind_address).await?;
+        socket.connect(address).await?;
+        Ok(Self {
+            socket,
+            key_prefix: key_prefix.to_string(),
+            dogstatsd_tags: config.dogstatsd_tags,
+            max_packet_bytes: config.max_packet_bytes as usize,
+            previous_counters: HashMap::new(),
+        })
+    }
+
+    /// Turn flat key/values into metrics. Counters need a previous value, so
+    /// they are only sent from the second run onwards.
+    fn build_metrics(
+        &mut self,
+        flat_stats: &BTreeMap<String, serde_json::Value>,
+        key_prefix: &str,
+        machine_names: &[&str],
+    ) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        for (key, value) in flat_stats {
+            // Skip non numeric values like version
+            let Some(value) = value.as_f64() else {
+                continue;
+            };
+            let field = key.rsplit('.').next().unwrap_or(key);
+            let metric_type = match crate::json::is_counter_field(field) {
+                true => MetricType::Counter,
+                false => MetricType::Gauge,
+            };
+            let value = match metric_type {
+                MetricType::Gauge => value,
+                MetricType::Counter => {
+                    let previous = self.previous_counters.insert(key.clone(), value);
+                    match previous {
+                        None => continue,
+                        // The counter reset (e.g. the service restarted)
+                        Some(previous) if value < previous => value,
+                        Some(previous) => value - previous,
+                    }
+                }
+            };
+
+            let (name, tags) = match self.dogstatsd_tags {
+                true => {
+                    let unprefixed_key = match key_prefix.is_empty() {
+                        true => key.as_str(),
+                        false => key
+                            .strip_prefix(&format!("{key_prefix}."))
+                            .unwrap_or(key.as_str()),
+                    };
+                    let (name, tags) = split_tags(unprefixed_key, machine_names);
+                    (crate::json::gen_base_metric_key(key_prefix, &name), tags)
+                }
+                false => (key.clone(), Vec::new()),
+            };
+            metrics.push(Metric {
+                name,
+                value,
+                metric_type,
+                tags,
+            });
+        }
+        metrics
+    }
+
+    /// Send all stats, batching lines into as few datagrams as fit
+    pub async fn send(&mut self, stats: &MonitordStats) -> Result<(), MonitordStatsdError> {
+        let key_prefix = self.key_prefix.clone();
+        let flat_stats = crate::json::flatten_stats(stats, &key_prefix);
+        let machine_names: Vec<&str> = stats.machines.keys().map(|m| m.as_str()).collect();
+        let lines: Vec<String> = self
+            .build_metrics(&flat_stats, &key_prefix, &machine_names)
+            .iter()
+            .flat_map(|m| m.render())
+            .collect();
+        let packets = batch_lines(&lines, self.max_packet_bytes);
+        debug!(
+            "Sending {} StatsD lines in {} packets",
+            lines.len(),
+            packets.len()
+        );
+        for packet in packets {
+            self.socket.send(packet.as_bytes()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Join lines with newlines into packets no bigger than `max_packet_bytes`.
+/// A single line over the limit is sent on its own.
+fn batch_lines(lines: &[String], max_packet_bytes: usize) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut packet = String::new();
+    for line in lines {
+        if !packet.is_empty() && packet.len() + 1 + line.len() > max_packet_bytes {
+            packets.push(std::mem::take(&mut packet));
+        }
+        if !packet.is_empty() {
+            packet.push('\n');
+        }
+        packet.push_str(line);
+    }
+    if !packet.is_empty() {
+        packets.push(packet);
+    }
+    packets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn return_monitord_stats() -> MonitordStats {
+        let mut stats = MonitordStats::default();
+        stats.units.service_stats.insert(
+            String::from("foo.service"),
+            crate::units::ServiceStats {
+                nrestarts: 2,
+                memory_current: 1024,
+                status_errno: -5,
+                ..Default::default()
+            },
+        );
+        let mut machine = crate::MachineStats::default();
+        machine.units.failed_units = 1;
+        stats.machines.insert(String::from("web.prod"), machine);
+        stats
+    }
+
+    #[test]
+    fn test_split_tags() {
+        let machines = ["web.prod"];
+        assert_eq!(
+            (
+                String::from("services.nrestarts"),
+                vec![(String::from("unit"), String::from("foo.bar.service"))]
+            ),
+            split_tags("services.foo.bar.service.nrestarts", &machines)
+        );
+        assert_eq!(
+            (
+                String::from("machines.units.failed_units"),
+                vec![(String::from("machine"), String::from("web.prod"))]
+            ),
+            split_tags("machines.web.prod.units.failed_units", &machines)
+        );
+        assert_eq!(
+            (
+                String::from("boot.blame"),
+                vec![(String::from("unit"), String::from("foo.service"))]
+            ),
+            split_tags("boot.blame.foo.service", &machines)
+        );
+        assert_eq!(
+            (
+                String::from("pressure.io_full_avg10"),
+                vec![(String::from("slice"), String::from("system.slice"))]
+            ),
+            split_tags("pressure.system.slice.io_full_avg10", &machines)
+        );
+        assert_eq!(
+            (String::from("networkd.managed_interfaces"), vec![]),
+            split_tags("networkd.managed_interfaces", &machines)
+        );
+    }
+
+    #[test]
+    fn test_metric_render() {
+        let gauge = Metric {
+            name: String::from("services.status_errno"),
+            value: -5.0,
+            metric_type: MetricType::Gauge,
+            tags: vec![(String::from("unit"), String::from("foo.service"))],
+        };
+        assert_eq!(
+            vec![
+                String::from("services.status_errno:0|g|#unit:foo.service"),
+                String::from("services.status_errno:-5|g|#unit:foo.service"),
+            ],
+            gauge.render()
+        );
+        let counter = Metric {
+            name: String::from("services.foo.service.nrestarts"),
+            value: 1.0,
+            metric_type: MetricType::Counter,
+            tags: vec![],
+        };
+        assert_eq!(
+            vec![String::from("services.foo.service.nrestarts:1|c")],
+            counter.render()
+        );
+    }
+
+    #[test]
+    fn test_batch_lines() {
+        let lines: Vec<String> = vec!["a".repeat(10), "b".repeat(10), "c".repeat(30)];
+        assert_eq!(
+            vec![
+                format!("{}\n{}", "a".repeat(10), "b".repeat(10)),
+                "c".repeat(30)
+            ],
+            batch_lines(&lines, 25)
+        );
+        assert!(batch_lines(&[], 25).is_empty());
+    }
+
+    /// Read packets until the socket has been quiet for a moment
+    async fn recv_all(receiver: &UdpSocket) -> String {
+        let mut buf = vec![0u8; 65536];
+        let mut lines = Vec::new();
+        while let Ok(Ok(len)) = tokio::time::timeout(
+            std::time::Duration::from_millis(100),
+            receiver.recv(&mut buf),
+        )
+        .await
+        {
+            lines.push(String::from_utf8_lossy(&buf[..len]).into_owned());
+        }
+        lines.join("\n")
+    }
+
+    #[tokio::test]
+    async fn test_send_to_udp_socket() {
+        let receiver = UdpSocket::bind("127.0.0.1:0")
+            .await
+            .expect("Unable to bind receiver");
+        let config = StatsdConfig {
+            enabled: true,
+            address: receiver.local_addr().expect("No local address").to_string(),
+            dogstatsd_tags: true,
+            max_packet_bytes: 1432,
+        };
+        let mut sink = StatsdSink::new(&config, "monitord")
+            .await
+            .expect("Unable to create sink");
+
+        let mut stats = return_monitord_stats();
+        sink.send(&stats).await.expect("Unable to send stats");
+        let packet = recv_all(&receiver).await;
+        assert!(packet
+            .lines()
+            .any(|l| l == "monitord.services.memory_current:1024|g|#unit:foo.service"));
+        assert!(packet
+            .lines()
+            .any(|l| l == "monitord.machines.units.failed_units:1|g|#machine:web.prod"));
+        // No counters on the first run
+        assert!(!packet.contains("|c"));
+
+        stats
+            .units
+            .service_stats
+            .get_mut("foo.service")
+            .expect("No foo.service")
+            .nrestarts = 5;
+        sink.send(&stats).await.expect("Unable to send stats");
+        let packet = recv_all(&receiver).await;
+        assert!(packet
+            .lines()
+            .any(|l| l == "monitord.services.nrestarts:3|c|#unit:foo.service"));
+    }
+}
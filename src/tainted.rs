@@ -0,0 +1,113 @@
+//! # tainted module
+//!
+//! Parses the systemd manager's `Tainted` property, a colon separated list of
+//! reasons the system runs in an unsupported or unusual configuration
+//! (e.g. `unmerged-usr:cgroupsv1:local-hwclock`).
+//! Ref: <https://www.freedesktop.org/software/systemd/man/org.freedesktop.systemd1.html#Properties>
+
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::dbus::zbus_systemd::ManagerProxy;
+use crate::MachineStats;
+
+/// Known taint flags. Flags from newer systemd versions go in `unknown`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TaintedStats {
+    /// /proc/cgroups is missing
+    pub cgroups_missing: bool,
+    /// Running with the legacy or hybrid cgroup hierarchy
+    pub cgroupsv1: bool,
+    /// The RTC is in local time
+    pub local_hwclock: bool,
+    /// The kernel is older than systemd supports
+    pub old_kernel: bool,
+    pub overflowgid_not_65534: bool,
+    pub overflowuid_not_65534: bool,
+    /// The user namespace GID range is smaller than 0..4294967294
+    pub short_gid_range: bool,
+    /// The user namespace UID range is smaller than 0..4294967294
+    pub short_uid_range: bool,
+    /// /usr is a separate file system not mounted by the initrd (older systemd)
+    pub split_usr: bool,
+    /// The OS release is past its SUPPORT_END= date
+    pub support_ended: bool,
+    /// /usr/sbin is not a symlink to /usr/bin
+    pub unmerged_bin: bool,
+    /// /bin, /sbin and /lib* are not symlinks into /usr
+    pub unmerged_usr: bool,
+    /// /var/run is not a symlink to /run
+    pub var_run_bad: bool,
+    /// Flags monitord doesn't know about
+    pub unknown: Vec<String>,
+}
+
+impl From<&str> for TaintedStats {
+    fn from(tainted: &str) -> Self {
+        let mut stats = TaintedStats::default();
+        for flag in tainted.split(':').filter(|f| !f.is_empty()) {
+            match flag {
+                "cgroups-missing" => stats.cgroups_missing = true,
+                "cgroupsv1" => stats.cgroupsv1 = true,
+                "local-hwclock" => stats.local_hwclock = true,
+                "old-kernel" => stats.old_kernel = true,
+                "overflowgid-not-65534" => stats.overflowgid_not_65534 = true,
+                "overflowuid-not-65534" => stats.overflowuid_not_65534 = true,
+                "short-gid-range" => stats.short_gid_range = true,
+                "short-uid-range" => stats.short_uid_range = true,
+                "split-usr" => stats.split_usr = true,
+                "support-ended" => stats.support_ended = true,
+                "unmerged-bin" => stats.unmerged_bin = true,
+                "unmerged-usr" => stats.unmerged_usr = true,
+                "var-run-bad" => stats.var_run_bad = true,
+                _ => stats.unknown.push(flag.to_string()),
+            }
+        }
+        stats
+    }
+}
+
+pub async fn get_tainted_stats(connection: &zbus::Connection) -> zbus::Result<TaintedStats> {
+    let manager = ManagerProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let tainted = manager.tainted().await?;
+    debug!("systemd Tainted: {:?}", tainted);
+    Ok(TaintedStats::from(tainted.as_str()))
+}
+
+/// Async wrapper than can update tainted stats when passed a locked struct
+pub async fn update_tainted_stats(
+    connection: zbus::Connection,
+    locked_machine_stats: Arc<RwLock<MachineStats>>,
+) -> anyhow::Result<()> {
+    let tainted_stats = get_tainted_stats(&connection)
+        .await
+        .map_err(|e| anyhow::anyhow!("Error getting tainted flags: {:?}", e))?;
+    let mut machine_stats = locked_machine_stats.write().await;
+    machine_stats.tainted = Some(tainted_stats);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tainted() {
+        assert_eq!(TaintedStats::default(), TaintedStats::from(""));
+        assert_eq!(
+            TaintedStats {
+                unmerged_usr: true,
+                cgroupsv1: true,
+                local_hwclock: true,
+                unknown: vec![String::from("new-taint")],
+                ..Default::default()
+            },
+            TaintedStats::from("unmerged-usr:cgroupsv1:new-taint:local-hwclock")
+        );
+    }
+}
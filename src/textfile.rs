@@ -0,0 +1,127 @@
+//! # textfile module
+//!
+//! Write Prometheus formatted metrics to a `.prom` file for node_exporter's
+//! textfile collector. The file is written to a temporary file in the same
+//! directory then renamed into place so a partial file is never read.
+//! Ref: <https://github.com/prometheus/node_exporter#textfile-collector>
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+
+use crate::MonitordStats;
+
+#[derive(Error, Debug)]
+pub enum MonitordTextfileError {
+    #[error("Textfile I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Textfile path '{0}' has no file name")]
+    InvalidPath(PathBuf),
+}
+
+/// Temporary file next to `path`. node_exporter only reads `*.prom` files so
+/// the `.tmp` suffix keeps it from being collected mid write.
+fn temp_file_path(path: &Path) -> Result<PathBuf, MonitordTextfileError> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| MonitordTextfileError::InvalidPath(path.to_path_buf()))?;
+    Ok(path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    )))
+}
+
+/// Atomically replace `path` with `content`
+async fn write_atomic(path: &Path, content: &[u8]) -> Result<(), MonitordTextfileError> {
+    let temp_path = temp_file_path(path)?;
+    let write_result = async {
+        let mut temp_file = tokio::fs::File::create(&temp_path).await?;
+        temp_file.write_all(content).await?;
+        temp_file.sync_all().await?;
+        tokio::fs::rename(&temp_path, path).await
+    }
+    .await;
+    if write_result.is_err() {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+    }
+    Ok(write_result?)
+}
+
+/// Render stats in Prometheus format and atomically write them to `path`
+pub async fn write_textfile(
+    path: &Path,
+    stats: &MonitordStats,
+) -> Result<(), MonitordTextfileError> {
+    let last_run_timestamp_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default();
+    let content = crate::prometheus::render_with_run_timestamp(stats, last_run_timestamp_secs);
+    write_atomic(path, content.as_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_textfile() {
+        let dir = tempfile::tempdir().expect("Unable to make temp dir");
+        let path = dir.path().join("monitord.prom");
+        tokio::fs::write(&path, "stale\n")
+            .await
+            .expect("Unable to write stale file");
+
+        let stats = MonitordStats {
+            stat_collection_run_time_ms: 69.0,
+            ..Default::default()
+        };
+        write_textfile(&path, &stats)
+            .await
+            .expect("Unable to write textfile");
+
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .expect("Unable to read textfile");
+        assert!(!content.contains("stale"));
+        assert!(content.contains("# TYPE monitord_last_run_timestamp_seconds gauge\n"));
+        assert!(content.contains("monitord_stat_collection_run_time_ms 69\n"));
+        // Only the final file is left behind
+        let mut entries = std::fs::read_dir(dir.path())
+            .expect("Unable to read temp dir")
+            .map(|e| e.expect("Bad dir entry").file_name())
+            .collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(vec![std::ffi::OsString::from("monitord.prom")], entries);
+    }
+
+    #[tokio::test]
+    async fn test_write_textfile_missing_dir_fails_cleanly() {
+        let dir = tempfile::tempdir().expect("Unable to make temp dir");
+        let path = dir.path().join("missing").join("monitord.prom");
+        assert!(write_textfile(&path, &MonitordStats::default())
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_temp_file_path() {
+        let temp_path =
+            temp_file_path(Path::new("/var/lib/node_exporter/monitord.prom")).expect("No path");
+        assert_eq!(
+            Some(Path::new("/var/lib/node_exporter")),
+            temp_path.parent()
+        );
+        assert!(temp_path
+            .file_name()
+            .expect("No file name")
+            .to_string_lossy()
+            .ends_with(".tmp"));
+        assert!(temp_file_path(Path::new("/")).is_err());
+    }
+}
@@ -7,6 +7,11 @@ use struct_field_names_as_array::FieldNamesAsArray;
 use thiserror::Error;
 use tracing::error;
 
+use crate::dbus::properties::GetAllTimings;
+use crate::dbus::properties::Properties;
+use crate::dbus::properties::TIMER_INTERFACE;
+use crate::dbus::properties::UNIT_INTERFACE;
+
 #[derive(Error, Debug)]
 pub enum MonitordTimerError {
     #[error("Timer D-Bus error: {0}")]
@@ -46,23 +51,46 @@ pub struct TimerStats {
 
 pub const TIMER_STATS_FIELD_NAMES: &[&str] = &TimerStats::FIELD_NAMES_AS_ARRAY;
 
-#[tracing::instrument(level = "debug", skip(connection))]
+/// Build timer stats from the Timer interface properties and, when the timer
+/// has one, the Unit interface properties of the service it triggers
+fn timer_stats_from_properties(
+    timer: &Properties,
+    service_unit: Option<&Properties>,
+) -> zbus::Result<TimerStats> {
+    let mut timer_stats = TimerStats {
+        accuracy_usec: timer.get("AccuracyUSec")?,
+        fixed_random_delay: timer.get("FixedRandomDelay")?,
+        last_trigger_usec: timer.get("LastTriggerUSec")?,
+        last_trigger_usec_monotonic: timer.get("LastTriggerUSecMonotonic")?,
+        next_elapse_usec_monotonic: timer.get("NextElapseUSecMonotonic")?,
+        next_elapse_usec_realtime: timer.get("NextElapseUSecRealtime")?,
+        persistent: timer.get("Persistent")?,
+        randomized_delay_usec: timer.get("RandomizedDelayUSec")?,
+        remain_after_elapse: timer.get("RemainAfterElapse")?,
+        ..Default::default()
+    };
+    if let Some(service_unit) = service_unit {
+        timer_stats.service_unit_last_state_change_usec =
+            service_unit.get("StateChangeTimestamp")?;
+        timer_stats.service_unit_last_state_change_usec_monotonic =
+            service_unit.get("StateChangeTimestampMonotonic")?;
+    }
+    Ok(timer_stats)
+}
+
+#[tracing::instrument(level = "debug", skip(connection, get_all_timings))]
 pub async fn collect_timer_stats(
     connection: &zbus::Connection,
     unit: &crate::units::ListedUnit,
+    get_all_timings: &mut GetAllTimings,
 ) -> Result<TimerStats, MonitordTimerError> {
-    let mut timer_stats = TimerStats::default();
+    let timer = Properties::get_all(connection, &unit.unit_object_path, TIMER_INTERFACE).await?;
+    get_all_timings.record(&timer);
 
-    let pt = crate::dbus::zbus_timer::TimerProxy::builder(connection)
-        .cache_properties(zbus::proxy::CacheProperties::No)
-        .path(unit.unit_object_path.clone())?
-        .build()
-        .await?;
     // Get service unit name to check when it last ran to ensure
     // we are triggers the configured service with times set
-    let service_unit = pt.unit().await?;
-    let mut service_unit_last_state_change_usec: Result<u64, zbus::Error> = Ok(0);
-    let mut service_unit_last_state_change_usec_monotonic: Result<u64, zbus::Error> = Ok(0);
+    let service_unit: String = timer.get("Unit")?;
+    let mut service_unit_properties = None;
     if service_unit.is_empty() {
         error!("{}: No service unit name found for timer.", unit.name);
     } else {
@@ -72,67 +100,24 @@ pub async fn collect_timer_stats(
             .build()
             .await?;
         let service_unit_path = mp.get_unit(&service_unit).await?;
-        // Create a UnitProxy with the unit path to async get the two counters we want
-        let up = crate::dbus::zbus_unit::UnitProxy::builder(connection)
-            .cache_properties(zbus::proxy::CacheProperties::No)
-            .path(service_unit_path)?
-            .build()
-            .await?;
-
-        (
-            service_unit_last_state_change_usec,
-            service_unit_last_state_change_usec_monotonic,
-        ) = tokio::join!(
-            up.state_change_timestamp(),
-            up.state_change_timestamp_monotonic(),
-        );
+        let properties =
+            Properties::get_all(connection, &service_unit_path, UNIT_INTERFACE).await?;
+        get_all_timings.record(&properties);
+        service_unit_properties = Some(properties);
     }
-    timer_stats.service_unit_last_state_change_usec = service_unit_last_state_change_usec?;
-    timer_stats.service_unit_last_state_change_usec_monotonic =
-        service_unit_last_state_change_usec_monotonic?;
-
-    // Use tokio::join! without tokio::spawn to avoid per-task allocation overhead.
-    // These all share the same D-Bus connection so spawn adds no parallelism benefit.
-    let (
-        accuracy_usec,
-        fixed_random_delay,
-        last_trigger_usec,
-        last_trigger_usec_monotonic,
-        persistent,
-        next_elapse_usec_monotonic,
-        next_elapse_usec_realtime,
-        randomized_delay_usec,
-        remain_after_elapse,
-    ) = tokio::join!(
-        pt.accuracy_usec(),
-        pt.fixed_random_delay(),
-        pt.last_trigger_usec(),
-        pt.last_trigger_usec_monotonic(),
-        pt.persistent(),
-        pt.next_elapse_usec_monotonic(),
-        pt.next_elapse_usec_realtime(),
-        pt.randomized_delay_usec(),
-        pt.remain_after_elapse(),
-    );
-
-    timer_stats.accuracy_usec = accuracy_usec?;
-    timer_stats.fixed_random_delay = fixed_random_delay?;
-    timer_stats.last_trigger_usec = last_trigger_usec?;
-    timer_stats.last_trigger_usec_monotonic = last_trigger_usec_monotonic?;
-    timer_stats.persistent = persistent?;
-    timer_stats.next_elapse_usec_monotonic = next_elapse_usec_monotonic?;
-    timer_stats.next_elapse_usec_realtime = next_elapse_usec_realtime?;
-    timer_stats.randomized_delay_usec = randomized_delay_usec?;
-    timer_stats.remain_after_elapse = remain_after_elapse?;
 
-    Ok(timer_stats)
+    Ok(timer_stats_from_properties(
+        &timer,
+        service_unit_properties.as_ref(),
+    )?)
 }
 
 /// Collect all timer stats via D-Bus and return them ready to merge into unit stats.
 ///
 /// Used when unit stats were collected via varlink (which doesn't yet expose timer
 /// properties) so that `timers.*`, `timer_persistent_units`, and
-/// `timer_remain_after_elapse` match the D-Bus output.
+/// `timer_remain_after_elapse` match the D-Bus output. The timer fetch count and
+/// `GetAll` timings are returned in `collection_timings`.
 pub async fn collect_all_timers_dbus(
     connection: &zbus::Connection,
     config: &crate::config::Config,
@@ -152,6 +137,7 @@ pub async fn collect_all_timers_dbus(
 
     let mut stats = crate::units::SystemdUnitStats::default();
     let mut timer_stats_map = HashMap::new();
+    let mut get_all_timings = GetAllTimings::default();
 
     for unit_raw in units {
         let unit: crate::units::ListedUnit = unit_raw.into();
@@ -165,7 +151,7 @@ pub async fn collect_all_timers_dbus(
         if !config.timers.allowlist.is_empty() && !config.timers.allowlist.contains(&unit.name) {
             continue;
         }
-        match collect_timer_stats(connection, &unit).await {
+        match collect_timer_stats(connection, &unit, &mut get_all_timings).await {
             Ok(ts) => {
                 if ts.persistent {
                     stats.timer_persistent_units += 1;
@@ -174,6 +160,7 @@ pub async fn collect_all_timers_dbus(
                     stats.timer_remain_after_elapse += 1;
                 }
                 timer_stats_map.insert(unit.name.clone(), ts);
+                stats.collection_timings.timer_dbus_fetches += 1;
             }
             Err(err) => {
                 error!("Failed to get {} stats: {:#?}", &unit.name, err);
@@ -182,5 +169,95 @@ pub async fn collect_all_timers_dbus(
     }
 
     stats.timer_stats = timer_stats_map;
+    stats.collection_timings.properties_get_all_calls = get_all_timings.calls;
+    stats.collection_timings.properties_get_all_ms = get_all_timings.elapsed_ms;
     Ok(stats)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use zbus::zvariant::OwnedValue;
+    use zbus::zvariant::Value;
+
+    fn timer_properties() -> Properties {
+        Properties::new(
+            TIMER_INTERFACE,
+            HashMap::from([
+                (
+                    String::from("AccuracyUSec"),
+                    OwnedValue::from(60_000_000_u64),
+                ),
+                (String::from("FixedRandomDelay"), OwnedValue::from(false)),
+                (
+                    String::from("LastTriggerUSec"),
+                    OwnedValue::from(1_700_000_000_000_000_u64),
+                ),
+                (
+                    String::from("LastTriggerUSecMonotonic"),
+                    OwnedValue::from(5_000_000_u64),
+                ),
+                (
+                    String::from("NextElapseUSecMonotonic"),
+                    OwnedValue::from(0_u64),
+                ),
+                (
+                    String::from("NextElapseUSecRealtime"),
+                    OwnedValue::from(1_700_000_086_400_000_000_u64),
+                ),
+                (String::from("Persistent"), OwnedValue::from(true)),
+                (
+                    String::from("RandomizedDelayUSec"),
+                    OwnedValue::from(3_600_000_000_u64),
+                ),
+                (String::from("RemainAfterElapse"), OwnedValue::from(true)),
+                (
+                    String::from("Unit"),
+                    OwnedValue::try_from(Value::from("logrotate.service")).expect("owned str"),
+                ),
+            ]),
+        )
+    }
+
+    #[test]
+    fn test_timer_stats_from_properties() {
+        let service_unit = Properties::new(
+            UNIT_INTERFACE,
+            HashMap::from([
+                (
+                    String::from("StateChangeTimestamp"),
+                    OwnedValue::from(1_700_000_000_100_000_u64),
+                ),
+                (
+                    String::from("StateChangeTimestampMonotonic"),
+                    OwnedValue::from(5_100_000_u64),
+                ),
+            ]),
+        );
+        assert_eq!(
+            TimerStats {
+                accuracy_usec: 60_000_000,
+                fixed_random_delay: false,
+                last_trigger_usec: 1_700_000_000_000_000,
+                last_trigger_usec_monotonic: 5_000_000,
+                next_elapse_usec_monotonic: 0,
+                next_elapse_usec_realtime: 1_700_000_086_400_000_000,
+                persistent: true,
+                randomized_delay_usec: 3_600_000_000,
+                remain_after_elapse: true,
+                service_unit_last_state_change_usec: 1_700_000_000_100_000,
+                service_unit_last_state_change_usec_monotonic: 5_100_000,
+            },
+            timer_stats_from_properties(&timer_properties(), Some(&service_unit)).unwrap()
+        );
+
+        // No service unit leaves its timestamps at 0
+        let timer_stats = timer_stats_from_properties(&timer_properties(), None).unwrap();
+        assert_eq!(0, timer_stats.service_unit_last_state_change_usec);
+        assert_eq!(0, timer_stats.service_unit_last_state_change_usec_monotonic);
+
+        // A missing property fails the timer, as a proxy getter would
+        assert!(timer_stats_from_properties(&Properties::default(), None).is_err());
+    }
+}
@@ -3,6 +3,8 @@
 //! Shared constants and enums for systemd unit states and operations.
 //! Reference: <https://www.freedesktop.org/software/systemd/man/org.freedesktop.systemd1.html>
 
+use std::str::FromStr;
+
 use int_enum::IntEnum;
 use serde_repr::*;
 use strum_macros::EnumIter;
@@ -64,6 +66,47 @@ pub enum SystemdUnitLoadState {
     not_found = 4,
 }
 
+/// Possible systemd service results enumerated. Why the service last
+/// stopped or failed, `success` if it didn't fail.
+#[allow(non_camel_case_types)]
+#[derive(
+    Serialize_repr,
+    Deserialize_repr,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    EnumIter,
+    EnumString,
+    IntEnum,
+    strum_macros::Display,
+)]
+#[repr(u8)]
+pub enum SystemdServiceResult {
+    #[default]
+    unknown = 0,
+    success = 1,
+    resources = 2,
+    protocol = 3,
+    timeout = 4,
+    exit_code = 5,
+    signal = 6,
+    core_dump = 7,
+    watchdog = 8,
+    start_limit_hit = 9,
+    oom_kill = 10,
+    exec_condition = 11,
+}
+
+/// Map a service's D-Bus `Result` (e.g. `start-limit-hit`) to the enum.
+/// Results from newer systemd versions are `unknown`.
+pub fn service_result_from_dbus(result: &str) -> SystemdServiceResult {
+    SystemdServiceResult::from_str(&result.replace('-', "_"))
+        .unwrap_or(SystemdServiceResult::unknown)
+}
+
 /// Check if we're a loaded unit and if so evaluate if we're active or not
 /// If we're not
 /// Only potentially mark unhealthy for LOADED units that are not active
@@ -167,6 +210,33 @@ mod tests {
     fn test_iterators() {
         assert!(SystemdUnitActiveState::iter().collect::<Vec<_>>().len() > 0);
         assert!(SystemdUnitLoadState::iter().collect::<Vec<_>>().len() > 0);
+        assert!(SystemdServiceResult::iter().count() > 0);
+    }
+
+    #[test]
+    fn test_service_result_from_dbus() {
+        assert_eq!(
+            SystemdServiceResult::success,
+            service_result_from_dbus("success")
+        );
+        assert_eq!(
+            SystemdServiceResult::oom_kill,
+            service_result_from_dbus("oom-kill")
+        );
+        assert_eq!(
+            SystemdServiceResult::start_limit_hit,
+            service_result_from_dbus("start-limit-hit")
+        );
+        assert_eq!(
+            SystemdServiceResult::exit_code,
+            service_result_from_dbus("exit-code")
+        );
+        assert_eq!(
+            SystemdServiceResult::unknown,
+            service_result_from_dbus("new-result")
+        );
+        // D-Bus uses dashes where the enum variants use underscores
+        assert!(SystemdServiceResult::from_str("start-limit-hit").is_err());
     }
 
     #[test]
@@ -0,0 +1,569 @@
+//! # unit_events module
+//!
+//! Event driven unit tracking for daemon mode. Polling `ListUnits` every
+//! `daemon_stats_refresh_secs` misses units that fail and restart between runs,
+//! so with `event_tracking` enabled we `Subscribe` to the systemd manager and
+//! follow `UnitNew`, `UnitRemoved`, `JobNew`, `JobRemoved` and per-unit
+//! `PropertiesChanged` signals, counting state transitions between outputs.
+//!
+//! Between polls the signals also keep the published `SystemdUnitStats`
+//! current: unit type and active state counts, `jobs_queued` and each
+//! `unit_states` entry's active state. Load state counts are only updated by
+//! the `ListUnits` poll, which replaces the stats with a fresh snapshot each
+//! run and seeds the last known state of units no signal has mentioned yet.
+//! Ref: <https://www.freedesktop.org/software/systemd/man/org.freedesktop.systemd1.html#Signals>
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use futures_util::stream::StreamExt;
+use tokio::sync::RwLock;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+use crate::dbus::zbus_systemd::ManagerProxy;
+use crate::unit_constants::is_unit_unhealthy_for_service;
+use crate::unit_constants::SystemdUnitActiveState;
+use crate::unit_constants::SYSTEMD_SERVICE_SUFFIX;
+use crate::units::SystemdUnitStats;
+use crate::units::UnitStateTransitions;
+use crate::MonitordStats;
+
+pub const UNIT_PATH_NAMESPACE: &str = "/org/freedesktop/systemd1/unit";
+const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+/// JobRemoved results that mean the job did not do what was asked
+const FAILED_JOB_RESULTS: &[&str] = &["failed", "timeout", "dependency"];
+const ALREADY_SUBSCRIBED_ERROR: &str = "org.freedesktop.systemd1.AlreadySubscribed";
+
+/// Decode a systemd unit object path back to the unit name.
+/// systemd escapes every byte outside `[A-Za-z0-9]` as `_xx` hex.
+pub fn unit_name_from_path(path: &str) -> Option<String> {
+    let escaped = path.strip_prefix(UNIT_PATH_NAMESPACE)?.strip_prefix('/')?;
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut chars = escaped.bytes();
+    while let Some(c) = chars.next() {
+        if c == b'_' {
+            let hex = [chars.next()?, chars.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(c);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// How a signal changed a unit, applied to the live `SystemdUnitStats`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnitChange {
+    /// A newly loaded unit, counted as inactive until it changes state
+    Loaded,
+    Removed {
+        active_state: Option<SystemdUnitActiveState>,
+    },
+    ActiveState {
+        /// None the first time a unit's state is seen
+        previous: Option<SystemdUnitActiveState>,
+        state: SystemdUnitActiveState,
+    },
+    JobNew,
+    JobRemoved,
+}
+
+fn active_state_count(
+    units: &mut SystemdUnitStats,
+    state: SystemdUnitActiveState,
+) -> Option<&mut u64> {
+    match state {
+        SystemdUnitActiveState::activating => Some(&mut units.activating_units),
+        SystemdUnitActiveState::active => Some(&mut units.active_units),
+        SystemdUnitActiveState::failed => Some(&mut units.failed_units),
+        SystemdUnitActiveState::inactive => Some(&mut units.inactive_units),
+        _ => None,
+    }
+}
+
+fn unit_type_count<'a>(units: &'a mut SystemdUnitStats, unit: &str) -> Option<&'a mut u64> {
+    match unit.rsplit('.').next() {
+        Some("automount") => Some(&mut units.automount_units),
+        Some("device") => Some(&mut units.device_units),
+        Some("mount") => Some(&mut units.mount_units),
+        Some("path") => Some(&mut units.path_units),
+        Some("scope") => Some(&mut units.scope_units),
+        Some("service") => Some(&mut units.service_units),
+        Some("slice") => Some(&mut units.slice_units),
+        Some("socket") => Some(&mut units.socket_units),
+        Some("target") => Some(&mut units.target_units),
+        Some("timer") => Some(&mut units.timer_units),
+        _ => None,
+    }
+}
+
+fn increment(count: Option<&mut u64>) {
+    if let Some(count) = count {
+        *count += 1;
+    }
+}
+
+fn decrement(count: Option<&mut u64>) {
+    if let Some(count) = count {
+        *count = count.saturating_sub(1);
+    }
+}
+
+impl UnitChange {
+    /// Update the unit counts, `jobs_queued` and `unit_states` between polls.
+    /// Load state counts are left to the next poll, which replaces all of
+    /// these with a fresh snapshot.
+    pub fn apply(
+        &self,
+        units: &mut SystemdUnitStats,
+        unit: &str,
+        ignore_inactive_oneshot_services: bool,
+    ) {
+        match *self {
+            UnitChange::Loaded => {
+                units.total_units += 1;
+                increment(unit_type_count(units, unit));
+                increment(active_state_count(units, SystemdUnitActiveState::inactive));
+            }
+            UnitChange::Removed { active_state } => {
+                units.total_units = units.total_units.saturating_sub(1);
+                decrement(unit_type_count(units, unit));
+                if let Some(active_state) = active_state {
+                    decrement(active_state_count(units, active_state));
+                }
+                units.unit_states.remove(unit);
+            }
+            UnitChange::ActiveState { previous, state } => {
+                // Without the previous state the counts can't be moved
+                let Some(previous) = previous else {
+                    return;
+                };
+                decrement(active_state_count(units, previous));
+                increment(active_state_count(units, state));
+                if let Some(unit_states) = units.unit_states.get_mut(unit) {
+                    unit_states.active_state = state;
+                    unit_states.time_in_state_usecs = unit_states.time_in_state_usecs.map(|_| 0);
+                    // Whether a service is oneshot is only known from the
+                    // poll, so leave an inactive service's health to it
+                    let maybe_oneshot = ignore_inactive_oneshot_services
+                        && unit.ends_with(SYSTEMD_SERVICE_SUFFIX)
+                        && state == SystemdUnitActiveState::inactive;
+                    if !maybe_oneshot {
+                        unit_states.unhealthy = is_unit_unhealthy_for_service(
+                            state,
+                            unit_states.load_state,
+                            false,
+                            false,
+                        );
+                    }
+                }
+            }
+            UnitChange::JobNew => units.jobs_queued += 1,
+            UnitChange::JobRemoved => units.jobs_queued = units.jobs_queued.saturating_sub(1),
+        }
+    }
+}
+
+/// Unit state kept between outputs from systemd signals
+#[derive(Debug, Default)]
+pub struct UnitEventState {
+    allowlist: HashSet<String>,
+    blocklist: HashSet<String>,
+    ignore_inactive_oneshot_services: bool,
+    active_states: HashMap<String, SystemdUnitActiveState>,
+    transitions: HashMap<String, UnitStateTransitions>,
+}
+
+impl UnitEventState {
+    /// Track units using the same filtering as unit state stats
+    pub fn new(config: &crate::config::UnitsConfig) -> Self {
+        Self {
+            allowlist: config.state_stats_allowlist.clone(),
+            blocklist: config.state_stats_blocklist.clone(),
+            ignore_inactive_oneshot_services: config.ignore_inactive_oneshot_services,
+            ..Default::default()
+        }
+    }
+
+    fn should_skip_unit(&self, unit: &str) -> bool {
+        self.blocklist.contains(unit)
+            || (!self.allowlist.is_empty() && !self.allowlist.contains(unit))
+    }
+
+    fn unit_transitions(&mut self, unit: &str) -> Option<&mut UnitStateTransitions> {
+        if self.should_skip_unit(unit) {
+            return None;
+        }
+        Some(self.transitions.entry(unit.to_string()).or_default())
+    }
+
+    /// Count a new ActiveState. Repeated signals for the same state are
+    /// ignored, and so is the first state seen for a unit the poll or a
+    /// `UnitNew` didn't tell us about, as we can't know what it changed from.
+    pub fn record_active_state(
+        &mut self,
+        unit: &str,
+        state: SystemdUnitActiveState,
+    ) -> Option<UnitChange> {
+        let previous = self.active_states.insert(unit.to_string(), state);
+        if previous == Some(state) {
+            return None;
+        }
+        if previous.is_some() {
+            if let Some(transitions) = self.unit_transitions(unit) {
+                transitions.record(state);
+            }
+        }
+        Some(UnitChange::ActiveState { previous, state })
+    }
+
+    /// A newly loaded unit starts out inactive
+    pub fn record_unit_new(&mut self, unit: &str) -> UnitChange {
+        debug!("Unit {} loaded", unit);
+        self.active_states
+            .entry(unit.to_string())
+            .or_insert(SystemdUnitActiveState::inactive);
+        UnitChange::Loaded
+    }
+
+    /// Forget the last state of a unit systemd garbage collected
+    pub fn record_unit_removed(&mut self, unit: &str) -> UnitChange {
+        UnitChange::Removed {
+            active_state: self.active_states.remove(unit),
+        }
+    }
+
+    pub fn record_job_new(&mut self, unit: &str) -> UnitChange {
+        if let Some(transitions) = self.unit_transitions(unit) {
+            transitions.jobs_started += 1;
+        }
+        UnitChange::JobNew
+    }
+
+    pub fn record_job_removed(&mut self, unit: &str, result: &str) -> UnitChange {
+        if FAILED_JOB_RESULTS.contains(&result) {
+            if let Some(transitions) = self.unit_transitions(unit) {
+                transitions.jobs_failed += 1;
+            }
+        }
+        UnitChange::JobRemoved
+    }
+
+    /// Move the counts since the previous output into the freshly polled
+    /// `units`, and take the state of units no signal has told us about yet
+    /// from it. Signals are newer than the poll, so they win.
+    pub fn apply(&mut self, units: &mut SystemdUnitStats) {
+        for (unit, unit_states) in &units.unit_states {
+            self.active_states
+                .entry(unit.clone())
+                .or_insert(unit_states.active_state);
+        }
+        units.unit_state_transitions = std::mem::take(&mut self.transitions);
+    }
+}
+
+/// Ask systemd to send unit and job signals to this connection. Several
+/// trackers share the connection so already being subscribed is fine.
+pub(crate) async fn subscribe(manager: &ManagerProxy<'_>) -> zbus::Result<()> {
+    match manager.subscribe().await {
+        Err(zbus::Error::MethodError(name, _, _)) if name.as_str() == ALREADY_SUBSCRIBED_ERROR => {
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Handle one org.freedesktop.DBus.Properties.PropertiesChanged for a unit
+fn handle_properties_changed(
+    state: &mut UnitEventState,
+    msg: &zbus::Message,
+) -> anyhow::Result<Option<(String, UnitChange)>> {
+    let Some(signal) = zbus::fdo::PropertiesChanged::from_message(msg.clone()) else {
+        return Ok(None);
+    };
+    let args = signal.args()?;
+    if args.interface_name.as_str() != UNIT_INTERFACE {
+        return Ok(None);
+    }
+    let Some(active_state) = args.changed_properties.get("ActiveState") else {
+        return Ok(None);
+    };
+    let header = msg.header();
+    let Some(unit) = header.path().and_then(|p| unit_name_from_path(p.as_str())) else {
+        return Ok(None);
+    };
+    let active_state: &str = active_state.downcast_ref()?;
+    let active_state =
+        SystemdUnitActiveState::from_str(active_state).unwrap_or(SystemdUnitActiveState::unknown);
+    Ok(state
+        .record_active_state(&unit, active_state)
+        .map(|change| (unit, change)))
+}
+
+/// Apply `change` to the published unit stats. Only called once the
+/// `UnitEventState` lock is released, as the main loop takes the two locks
+/// in the opposite order.
+async fn update_stats(
+    locked_stats: &RwLock<MonitordStats>,
+    unit: &str,
+    change: UnitChange,
+    ignore_inactive_oneshot_services: bool,
+) {
+    change.apply(
+        &mut locked_stats.write().await.units,
+        unit,
+        ignore_inactive_oneshot_services,
+    );
+}
+
+/// Subscribe to systemd signals and update `state` and the unit stats in
+/// `locked_stats` until the bus goes away. A signal that can't be decoded is
+/// logged and skipped.
+pub async fn track_unit_events(
+    connection: zbus::Connection,
+    state: Arc<RwLock<UnitEventState>>,
+    locked_stats: Arc<RwLock<MonitordStats>>,
+) -> anyhow::Result<()> {
+    let ignore_oneshot = state.read().await.ignore_inactive_oneshot_services;
+    let manager = ManagerProxy::builder(&connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let mut unit_new = manager.receive_unit_new().await?;
+    let mut unit_removed = manager.receive_unit_removed().await?;
+    let mut job_new = manager.receive_job_new().await?;
+    let mut job_removed = manager.receive_job_removed().await?;
+    let rule = zbus::MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .sender("org.freedesktop.systemd1")?
+        .interface("org.freedesktop.DBus.Properties")?
+        .member("PropertiesChanged")?
+        .path_namespace(UNIT_PATH_NAMESPACE)?
+        .add_arg(UNIT_INTERFACE)?
+        .build();
+    let mut properties_changed =
+        zbus::MessageStream::for_match_rule(rule, &connection, None).await?;
+    // systemd only sends unit and job signals to subscribed clients
+    subscribe(&manager).await?;
+    info!("Tracking systemd unit events");
+
+    loop {
+        tokio::select! {
+            Some(signal) = unit_new.next() => match signal.args() {
+                Ok(args) => {
+                    let change = state.write().await.record_unit_new(args.id);
+                    update_stats(&locked_stats, args.id, change, ignore_oneshot).await;
+                }
+                Err(err) => warn!("Unable to decode UnitNew: {:?}", err),
+            },
+            Some(signal) = unit_removed.next() => match signal.args() {
+                Ok(args) => {
+                    let change = state.write().await.record_unit_removed(args.id);
+                    update_stats(&locked_stats, args.id, change, ignore_oneshot).await;
+                }
+                Err(err) => warn!("Unable to decode UnitRemoved: {:?}", err),
+            },
+            Some(signal) = job_new.next() => match signal.args() {
+                Ok(args) => {
+                    let change = state.write().await.record_job_new(args.unit);
+                    update_stats(&locked_stats, args.unit, change, ignore_oneshot).await;
+                }
+                Err(err) => warn!("Unable to decode JobNew: {:?}", err),
+            },
+            Some(signal) = job_removed.next() => match signal.args() {
+                Ok(args) => {
+                    let change = state.write().await.record_job_removed(args.unit, args.result);
+                    update_stats(&locked_stats, args.unit, change, ignore_oneshot).await;
+                }
+                Err(err) => warn!("Unable to decode JobRemoved: {:?}", err),
+            },
+            Some(msg) = properties_changed.next() => {
+                let result = match msg {
+                    Ok(msg) => handle_properties_changed(&mut *state.write().await, &msg),
+                    Err(err) => Err(err.into()),
+                };
+                match result {
+                    Ok(Some((unit, change))) => {
+                        update_stats(&locked_stats, &unit, change, ignore_oneshot).await
+                    }
+                    Ok(None) => {}
+                    Err(err) => warn!("Unable to handle unit PropertiesChanged: {:?}", err),
+                }
+            }
+            else => break,
+        }
+    }
+    Err(anyhow::anyhow!("systemd signal streams closed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_name_from_path() {
+        assert_eq!(
+            Some(String::from("sshd.service")),
+            unit_name_from_path("/org/freedesktop/systemd1/unit/sshd_2eservice")
+        );
+        assert_eq!(
+            Some(String::from("getty@tty1.service")),
+            unit_name_from_path("/org/freedesktop/systemd1/unit/getty_40tty1_2eservice")
+        );
+        assert_eq!(
+            Some(String::from("-.mount")),
+            unit_name_from_path("/org/freedesktop/systemd1/unit/_2d_2emount")
+        );
+        assert_eq!(None, unit_name_from_path("/org/freedesktop/systemd1"));
+        assert_eq!(
+            None,
+            unit_name_from_path("/org/freedesktop/systemd1/unit/bad_2")
+        );
+    }
+
+    #[test]
+    fn test_unit_event_state() {
+        let config = crate::config::UnitsConfig {
+            state_stats_blocklist: HashSet::from([String::from("noisy.service")]),
+            ..Default::default()
+        };
+        let mut state = UnitEventState::new(&config);
+        // The poll seeds the state foo.service was in
+        let mut units = SystemdUnitStats::default();
+        units.unit_states.insert(
+            String::from("foo.service"),
+            crate::units::UnitStates {
+                active_state: SystemdUnitActiveState::active,
+                ..Default::default()
+            },
+        );
+        state.apply(&mut units);
+        // Fail and restart between two outputs
+        for active_state in [
+            SystemdUnitActiveState::active,
+            SystemdUnitActiveState::failed,
+            SystemdUnitActiveState::failed,
+            SystemdUnitActiveState::activating,
+            SystemdUnitActiveState::active,
+        ] {
+            state.record_active_state("foo.service", active_state);
+            state.record_active_state("noisy.service", active_state);
+            // Unknown to the poll, so its first state isn't a transition
+            state.record_active_state("bar.service", active_state);
+        }
+        state.record_job_new("foo.service");
+        state.record_job_removed("foo.service", "failed");
+        state.record_job_removed("foo.service", "done");
+
+        state.apply(&mut units);
+        assert_eq!(
+            HashMap::from([
+                (
+                    String::from("foo.service"),
+                    UnitStateTransitions {
+                        activating: 1,
+                        active: 1,
+                        failed: 1,
+                        jobs_started: 1,
+                        jobs_failed: 1,
+                        ..Default::default()
+                    }
+                ),
+                (
+                    String::from("bar.service"),
+                    UnitStateTransitions {
+                        activating: 1,
+                        active: 1,
+                        failed: 1,
+                        ..Default::default()
+                    }
+                ),
+            ]),
+            units.unit_state_transitions
+        );
+
+        // Counts reset after each output but the last state is remembered
+        state.record_active_state("foo.service", SystemdUnitActiveState::active);
+        state.apply(&mut units);
+        assert!(units.unit_state_transitions.is_empty());
+        // A removed unit that comes back new starts out inactive
+        state.record_unit_removed("foo.service");
+        state.record_unit_new("foo.service");
+        state.record_active_state("foo.service", SystemdUnitActiveState::active);
+        state.apply(&mut units);
+        assert_eq!(1, units.unit_state_transitions["foo.service"].active);
+    }
+
+    #[test]
+    fn test_unit_change_apply() {
+        let mut units = SystemdUnitStats {
+            active_units: 1,
+            service_units: 1,
+            total_units: 1,
+            ..Default::default()
+        };
+        units.unit_states.insert(
+            String::from("foo.service"),
+            crate::units::UnitStates {
+                active_state: SystemdUnitActiveState::active,
+                load_state: crate::unit_constants::SystemdUnitLoadState::loaded,
+                unhealthy: false,
+                time_in_state_usecs: Some(42),
+            },
+        );
+
+        UnitChange::ActiveState {
+            previous: Some(SystemdUnitActiveState::active),
+            state: SystemdUnitActiveState::failed,
+        }
+        .apply(&mut units, "foo.service", false);
+        assert_eq!(0, units.active_units);
+        assert_eq!(1, units.failed_units);
+        let foo = &units.unit_states["foo.service"];
+        assert_eq!(SystemdUnitActiveState::failed, foo.active_state);
+        assert!(foo.unhealthy);
+        assert_eq!(Some(0), foo.time_in_state_usecs);
+
+        // An unknown previous state leaves the counts alone
+        UnitChange::ActiveState {
+            previous: None,
+            state: SystemdUnitActiveState::active,
+        }
+        .apply(&mut units, "bar.service", false);
+        assert_eq!(0, units.active_units);
+
+        // An inactive service may be an ignored oneshot, so the poll decides
+        UnitChange::ActiveState {
+            previous: Some(SystemdUnitActiveState::failed),
+            state: SystemdUnitActiveState::inactive,
+        }
+        .apply(&mut units, "foo.service", true);
+        assert_eq!(1, units.inactive_units);
+        assert!(units.unit_states["foo.service"].unhealthy);
+
+        UnitChange::Loaded.apply(&mut units, "bar.timer", false);
+        assert_eq!(2, units.total_units);
+        assert_eq!(1, units.timer_units);
+        assert_eq!(2, units.inactive_units);
+
+        UnitChange::Removed {
+            active_state: Some(SystemdUnitActiveState::inactive),
+        }
+        .apply(&mut units, "foo.service", false);
+        assert_eq!(1, units.total_units);
+        assert_eq!(0, units.service_units);
+        assert_eq!(1, units.inactive_units);
+        assert!(units.unit_states.is_empty());
+
+        UnitChange::JobNew.apply(&mut units, "bar.timer", false);
+        assert_eq!(1, units.jobs_queued);
+        UnitChange::JobRemoved.apply(&mut units, "bar.timer", false);
+        UnitChange::JobRemoved.apply(&mut units, "bar.timer", false);
+        assert_eq!(0, units.jobs_queued);
+    }
+}
@@ -4,6 +4,8 @@
 //! queued jobs. We also house service specific statistics and system unit states.
 
 use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Instant;
@@ -32,12 +34,20 @@ pub enum MonitordUnitsError {
     SystemTimeError(#[from] std::time::SystemTimeError),
 }
 
+use crate::cgroup::CgroupPressure;
+use crate::cgroup::CgroupStats;
+use crate::dbus::properties::GetAllTimings;
+use crate::dbus::properties::Properties;
+use crate::dbus::properties::SERVICE_INTERFACE;
+use crate::dbus::properties::UNIT_INTERFACE;
 use crate::timer::TimerStats;
+use crate::unit_constants::service_result_from_dbus;
 use crate::MachineStats;
 
 // Re-export the enums and function from unit_constants for backwards compatibility
 pub use crate::unit_constants::is_unit_unhealthy;
 pub use crate::unit_constants::is_unit_unhealthy_for_service;
+pub use crate::unit_constants::SystemdServiceResult;
 pub use crate::unit_constants::SystemdUnitActiveState;
 pub use crate::unit_constants::SystemdUnitLoadState;
 pub use crate::unit_constants::SYSTEMD_SERVICE_SUFFIX;
@@ -61,6 +71,16 @@ pub struct UnitsCollectionTimings {
     pub state_dbus_fetches: u64,
     /// Number of per-service D-Bus property fetches this run.
     pub service_dbus_fetches: u64,
+    /// Number of services whose CPU, memory, IO and task counters were read
+    /// from cgroupfs rather than D-Bus this run (`units.cgroup_fast_path`).
+    pub service_cgroup_reads: u64,
+    /// Number of `Properties.GetAll` calls for service and timer properties this run,
+    /// plus the unit calls made by boot blame and boot history. With varlink enabled
+    /// the service calls are not made so are not counted.
+    pub properties_get_all_calls: u64,
+    /// Summed duration of those `GetAll` calls. They run concurrently, so this
+    /// can exceed `per_unit_loop_ms`.
+    pub properties_get_all_ms: f64,
     /// Slowest units (by per-unit collection duration, descending) this run,
     /// truncated to `units.slowest_units_count`. Empty when disabled (count 0).
     pub slowest_units: Vec<(String, f64)>,
@@ -139,6 +159,9 @@ pub struct SystemdUnitStats {
     pub timer_stats: HashMap<String, TimerStats>,
     /// Per-unit active/load state tracking keyed by unit name
     pub unit_states: HashMap<String, UnitStates>,
+    /// Per-unit state transition counts since the previous output keyed by unit name.
+    /// Only populated in daemon mode with `event_tracking` enabled.
+    pub unit_state_transitions: HashMap<String, UnitStateTransitions>,
     /// Inner timing breakdown for this collector. Zero-valued before the first
     /// run completes or when the varlink path is taken.
     pub collection_timings: UnitsCollectionTimings,
@@ -156,26 +179,63 @@ pub struct ServiceStats {
     pub active_exit_timestamp: u64,
     /// Total CPU time consumed by this service's cgroup in nanoseconds
     pub cpuusage_nsec: u64,
+    /// How the main process last exited: 1 exited, 2 killed by a signal, 3
+    /// dumped core (CLD_* si_code). 0 if it has not exited
+    pub exec_main_code: i32,
+    /// PID of the main process, 0 when not running
+    pub exec_main_pid: u32,
+    /// Exit status or signal number of the main process's last exit,
+    /// depending on `exec_main_code`
+    pub exec_main_status: i32,
     /// Realtime timestamp (usec since epoch) when the unit most recently left the inactive state
     pub inactive_exit_timestamp: u64,
     /// Total bytes read from block I/O by this service's cgroup
     pub ioread_bytes: u64,
     /// Total number of block I/O read operations by this service's cgroup
     pub ioread_operations: u64,
+    /// Total bytes written to block I/O by this service's cgroup. None without IOAccounting=
+    pub iowrite_bytes: Option<u64>,
+    /// Total number of block I/O write operations by this service's cgroup. None without IOAccounting=
+    pub iowrite_operations: Option<u64>,
+    /// Bytes sent by this service's cgroup. None without IPAccounting=
+    pub ipegress_bytes: Option<u64>,
+    /// Packets sent by this service's cgroup. None without IPAccounting=
+    pub ipegress_packets: Option<u64>,
+    /// Bytes received by this service's cgroup. None without IPAccounting=
+    pub ipingress_bytes: Option<u64>,
+    /// Packets received by this service's cgroup. None without IPAccounting=
+    pub ipingress_packets: Option<u64>,
     /// Memory available to the service (MemoryAvailable from cgroup), in bytes
     pub memory_available: u64,
     /// Current memory usage of the service's cgroup in bytes
     pub memory_current: u64,
+    /// Highest memory usage of the service's cgroup in bytes
+    pub memory_peak: Option<u64>,
+    /// Current swap usage of the service's cgroup in bytes
+    pub memory_swap_current: Option<u64>,
+    /// Highest swap usage of the service's cgroup in bytes
+    pub memory_swap_peak: Option<u64>,
+    /// Current zswap usage of the service's cgroup in bytes
+    pub memory_zswap_current: Option<u64>,
     /// Number of times systemd has restarted this service (automatic restarts)
     pub nrestarts: u32,
     /// Current number of processes in this service's cgroup
     pub processes: u32,
+    /// PSI of this service's cgroup. Only collected with `[pressure] enabled`
+    pub pressure: Option<CgroupPressure>,
     /// Configured restart delay for this service in microseconds (RestartUSec)
     pub restart_usec: u64,
+    /// Delay before the next automatic restart in microseconds, including
+    /// RestartSteps= backoff (RestartUSecNext). None before systemd v254
+    pub restart_usec_next: Option<u64>,
+    /// Why the service last stopped or failed (see README Metric Value Reference)
+    pub result: SystemdServiceResult,
     /// Realtime timestamp (usec since epoch) of the most recent state change of any kind
     pub state_change_timestamp: u64,
     /// errno-style exit status code from the main process (0 = success)
     pub status_errno: i32,
+    /// Free form status the service last sent with sd_notify(STATUS=...)
+    pub status_text: String,
     /// Current number of tasks (threads) in this service's cgroup
     pub tasks_current: u64,
     /// Timeout in microseconds for the cleanup of resources after the service exits
@@ -203,6 +263,46 @@ pub struct UnitStates {
     pub time_in_state_usecs: Option<u64>,
 }
 
+/// Per-unit counts of systemd signals seen since the previous output when
+/// `event_tracking` is enabled in daemon mode. Catches units that fail and get
+/// restarted between collection runs.
+#[derive(
+    serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, FieldNamesAsArray, PartialEq,
+)]
+pub struct UnitStateTransitions {
+    /// Times the unit entered the "activating" state
+    pub activating: u64,
+    /// Times the unit entered the "active" state
+    pub active: u64,
+    /// Times the unit entered the "deactivating" state
+    pub deactivating: u64,
+    /// Times the unit entered the "failed" state
+    pub failed: u64,
+    /// Times the unit entered the "inactive" state
+    pub inactive: u64,
+    /// Times the unit entered the "reloading" state
+    pub reloading: u64,
+    /// Jobs queued for the unit (JobNew)
+    pub jobs_started: u64,
+    /// Jobs for the unit that finished with a failed, timeout or dependency result (JobRemoved)
+    pub jobs_failed: u64,
+}
+
+impl UnitStateTransitions {
+    /// Count the unit entering `state`
+    pub fn record(&mut self, state: SystemdUnitActiveState) {
+        match state {
+            SystemdUnitActiveState::activating => self.activating += 1,
+            SystemdUnitActiveState::active => self.active += 1,
+            SystemdUnitActiveState::deactivating => self.deactivating += 1,
+            SystemdUnitActiveState::failed => self.failed += 1,
+            SystemdUnitActiveState::inactive => self.inactive += 1,
+            SystemdUnitActiveState::reloading => self.reloading += 1,
+            SystemdUnitActiveState::unknown => (),
+        }
+    }
+}
+
 // Declare state types
 // Reference: https://www.freedesktop.org/software/systemd/man/org.freedesktop.systemd1.html
 // SubState can be unit-type-specific so can't enum
@@ -267,13 +367,110 @@ pub const SERVICE_FIELD_NAMES: &[&str] = &ServiceStats::FIELD_NAMES_AS_ARRAY;
 pub const UNIT_FIELD_NAMES: &[&str] = &SystemdUnitStats::FIELD_NAMES_AS_ARRAY;
 pub const UNIT_STATES_FIELD_NAMES: &[&str] = &UnitStates::FIELD_NAMES_AS_ARRAY;
 
-/// Pull out selected systemd service statistics
-#[tracing::instrument(level = "debug", skip(connection, object_path))]
+/// systemd returns u64::MAX for resource counters when accounting is off or
+/// the cgroup file is missing, and for unset times. Properties added in newer
+/// systemd versions (e.g. `MemoryPeak` in v255) are None rather than failing
+/// the whole service.
+fn optional_u64(value: zbus::Result<u64>) -> zbus::Result<Option<u64>> {
+    match value {
+        Ok(u64::MAX) => Ok(None),
+        Ok(value) => Ok(Some(value)),
+        Err(zbus::Error::FDO(err)) if matches!(*err, zbus::fdo::Error::UnknownProperty(_)) => {
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// The cgroupfs value when the fast path read it, else the D-Bus property
+fn cgroup_or_dbus(cgroup_value: Option<u64>, dbus_value: zbus::Result<u64>) -> zbus::Result<u64> {
+    match cgroup_value {
+        Some(value) => Ok(value),
+        None => dbus_value,
+    }
+}
+
+/// Decode `ServiceStats` from a unit's `Unit` and `Service` interface
+/// properties. Resource counters in `cgroup_stats` win over the D-Bus values.
+fn service_stats_from_properties(
+    unit: &Properties,
+    service: &Properties,
+    processes: usize,
+    cgroup_stats: &CgroupStats,
+) -> Result<ServiceStats, MonitordUnitsError> {
+    Ok(ServiceStats {
+        active_enter_timestamp: unit.get("ActiveEnterTimestamp")?,
+        active_exit_timestamp: unit.get("ActiveExitTimestamp")?,
+        cpuusage_nsec: cgroup_or_dbus(cgroup_stats.cpuusage_nsec, service.get("CPUUsageNSec"))?,
+        exec_main_code: service.get("ExecMainCode")?,
+        exec_main_pid: service.get("ExecMainPID")?,
+        exec_main_status: service.get("ExecMainStatus")?,
+        inactive_exit_timestamp: unit.get("InactiveExitTimestamp")?,
+        ioread_bytes: cgroup_or_dbus(cgroup_stats.ioread_bytes, service.get("IOReadBytes"))?,
+        ioread_operations: cgroup_or_dbus(
+            cgroup_stats.ioread_operations,
+            service.get("IOReadOperations"),
+        )?,
+        iowrite_bytes: optional_u64(cgroup_or_dbus(
+            cgroup_stats.iowrite_bytes,
+            service.get("IOWriteBytes"),
+        ))?,
+        iowrite_operations: optional_u64(cgroup_or_dbus(
+            cgroup_stats.iowrite_operations,
+            service.get("IOWriteOperations"),
+        ))?,
+        ipegress_bytes: optional_u64(service.get("IPEgressBytes"))?,
+        ipegress_packets: optional_u64(service.get("IPEgressPackets"))?,
+        ipingress_bytes: optional_u64(service.get("IPIngressBytes"))?,
+        ipingress_packets: optional_u64(service.get("IPIngressPackets"))?,
+        memory_current: cgroup_or_dbus(cgroup_stats.memory_current, service.get("MemoryCurrent"))?,
+        memory_available: service.get("MemoryAvailable")?,
+        memory_peak: optional_u64(cgroup_or_dbus(
+            cgroup_stats.memory_peak,
+            service.get("MemoryPeak"),
+        ))?,
+        memory_swap_current: optional_u64(service.get("MemorySwapCurrent"))?,
+        memory_swap_peak: optional_u64(service.get("MemorySwapPeak"))?,
+        memory_zswap_current: optional_u64(service.get("MemoryZSwapCurrent"))?,
+        nrestarts: service.get("NRestarts")?,
+        processes: processes.try_into()?,
+        pressure: None,
+        restart_usec: service.get("RestartUSec")?,
+        restart_usec_next: optional_u64(service.get("RestartUSecNext"))?,
+        result: service_result_from_dbus(&service.get::<String>("Result")?),
+        state_change_timestamp: unit.get("StateChangeTimestamp")?,
+        status_errno: service.get("StatusErrno")?,
+        status_text: service.get("StatusText")?,
+        tasks_current: cgroup_or_dbus(cgroup_stats.tasks_current, service.get("TasksCurrent"))?,
+        timeout_clean_usec: service.get("TimeoutCleanUSec")?,
+        watchdog_usec: service.get("WatchdogUSec")?,
+    })
+}
+
+/// A service's stats and the cgroupfs directory they can be read from
+struct ParsedService {
+    stats: ServiceStats,
+    /// None when the service has no cgroup (not running)
+    cgroup_dir: Option<PathBuf>,
+    /// Whether the resource counters came from cgroupfs
+    cgroup_read: bool,
+}
+
+/// Pull out selected systemd service statistics with one `GetAll` call per
+/// interface. With `cgroup_fast_path` the resource counters are read from
+/// the cgroup named by the `ControlGroup` property instead of D-Bus.
+#[tracing::instrument(
+    level = "debug",
+    skip(connection, object_path, cgroup_root, get_all_timings)
+)]
 async fn parse_service(
     connection: &zbus::Connection,
     name: &str,
     object_path: &OwnedObjectPath,
-) -> Result<ServiceStats, MonitordUnitsError> {
+    cgroup_root: &Path,
+    cgroup_fast_path: bool,
+    get_all_timings: &mut GetAllTimings,
+) -> Result<ParsedService, MonitordUnitsError> {
     debug!("Parsing service {} stats", name);
 
     let sp = crate::dbus::zbus_service::ServiceProxy::builder(connection)
@@ -281,70 +478,51 @@ async fn parse_service(
         .path(object_path.clone())?
         .build()
         .await?;
-    let up = crate::dbus::zbus_unit::UnitProxy::builder(connection)
-        .cache_properties(zbus::proxy::CacheProperties::No)
-        .path(object_path.clone())?
-        .build()
-        .await?;
 
-    // Use tokio::join! without tokio::spawn to avoid per-task allocation overhead.
-    // These all share the same D-Bus connection so spawn adds no parallelism benefit.
-    let (
-        active_enter_timestamp,
-        active_exit_timestamp,
-        cpuusage_nsec,
-        inactive_exit_timestamp,
-        ioread_bytes,
-        ioread_operations,
-        memory_current,
-        memory_available,
-        nrestarts,
-        processes,
-        restart_usec,
-        state_change_timestamp,
-        status_errno,
-        tasks_current,
-        timeout_clean_usec,
-        watchdog_usec,
-    ) = tokio::join!(
-        up.active_enter_timestamp(),
-        up.active_exit_timestamp(),
-        sp.cpuusage_nsec(),
-        up.inactive_exit_timestamp(),
-        sp.ioread_bytes(),
-        sp.ioread_operations(),
-        sp.memory_current(),
-        sp.memory_available(),
-        sp.nrestarts(),
+    // GetProcesses is a method so it can't be part of GetAll
+    let (unit_properties, service_properties, processes) = tokio::join!(
+        Properties::get_all(connection, object_path, UNIT_INTERFACE),
+        Properties::get_all(connection, object_path, SERVICE_INTERFACE),
         sp.get_processes(),
-        sp.restart_usec(),
-        up.state_change_timestamp(),
-        sp.status_errno(),
-        sp.tasks_current(),
-        sp.timeout_clean_usec(),
-        sp.watchdog_usec(),
     );
+    let unit_properties = unit_properties?;
+    let service_properties = service_properties?;
+    get_all_timings.record(&unit_properties);
+    get_all_timings.record(&service_properties);
+
+    let control_group: String = service_properties.get("ControlGroup")?;
+    let mut cgroup_dir = (!control_group.is_empty())
+        .then(|| crate::cgroup::cgroup_path(cgroup_root, &control_group));
+    let mut cgroup_stats = None;
+    if let (true, Some(dir)) = (cgroup_fast_path, &cgroup_dir) {
+        cgroup_stats = read_service_cgroup_stats(dir).await;
+        if cgroup_stats.is_none() {
+            debug!("{} cgroup not readable, using D-Bus for its stats", name);
+            cgroup_dir = None;
+        }
+    }
 
-    Ok(ServiceStats {
-        active_enter_timestamp: active_enter_timestamp?,
-        active_exit_timestamp: active_exit_timestamp?,
-        cpuusage_nsec: cpuusage_nsec?,
-        inactive_exit_timestamp: inactive_exit_timestamp?,
-        ioread_bytes: ioread_bytes?,
-        ioread_operations: ioread_operations?,
-        memory_current: memory_current?,
-        memory_available: memory_available?,
-        nrestarts: nrestarts?,
-        processes: processes?.len().try_into()?,
-        restart_usec: restart_usec?,
-        state_change_timestamp: state_change_timestamp?,
-        status_errno: status_errno?,
-        tasks_current: tasks_current?,
-        timeout_clean_usec: timeout_clean_usec?,
-        watchdog_usec: watchdog_usec?,
+    let stats = service_stats_from_properties(
+        &unit_properties,
+        &service_properties,
+        processes?.len(),
+        cgroup_stats.as_ref().unwrap_or(&CgroupStats::default()),
+    )?;
+    Ok(ParsedService {
+        stats,
+        cgroup_dir,
+        cgroup_read: cgroup_stats.is_some(),
     })
 }
 
+/// Read a service's resource counters from cgroupfs. None when the cgroup
+/// can't be read, e.g. the service stopped since its cgroup was cached.
+/// `cpu.stat` exists in every cgroup so it tells whether the cgroup is there.
+async fn read_service_cgroup_stats(cgroup_dir: &Path) -> Option<CgroupStats> {
+    let cgroup_stats = crate::cgroup::read_cgroup_stats(cgroup_dir).await;
+    cgroup_stats.cpuusage_nsec.map(|_| cgroup_stats)
+}
+
 #[tracing::instrument(level = "debug", skip(connection))]
 async fn get_time_in_state(
     connection: Option<&zbus::Connection>,
@@ -377,6 +555,15 @@ async fn get_time_in_state(
     }
 }
 
+/// Whether `unit_name` passes the `state_stats` allowlist and blocklist
+pub(crate) fn is_state_tracked(config: &crate::config::UnitsConfig, unit_name: &str) -> bool {
+    if config.state_stats_blocklist.contains(unit_name) {
+        debug!("Skipping state stats for {} due to blocklist", unit_name);
+        return false;
+    }
+    config.state_stats_allowlist.is_empty() || config.state_stats_allowlist.contains(unit_name)
+}
+
 /// Parse state of a unit into a `UnitStates` entry for the caller to merge.
 ///
 /// Returns `(did_dbus_fetch, entry)`: `did_dbus_fetch` is true when an actual
@@ -389,13 +576,7 @@ pub async fn parse_state(
     config: &crate::config::UnitsConfig,
     connection: Option<&zbus::Connection>,
 ) -> Result<(bool, Option<UnitStates>), MonitordUnitsError> {
-    if config.state_stats_blocklist.contains(&unit.name) {
-        debug!("Skipping state stats for {} due to blocklist", &unit.name);
-        return Ok((false, None));
-    }
-    if !config.state_stats_allowlist.is_empty()
-        && !config.state_stats_allowlist.contains(&unit.name)
-    {
+    if !is_state_tracked(config, &unit.name) {
         return Ok((false, None));
     }
     let active_state = SystemdUnitActiveState::from_str(&unit.active_state)
@@ -623,6 +804,8 @@ struct PerUnitOutcome {
     unit_states_entry: Option<UnitStates>,
     state_dbus_fetch: bool,
     service_stats_entry: Option<ServiceStats>,
+    service_cgroup_read: bool,
+    get_all_timings: GetAllTimings,
     timer_stats_entry: Option<TimerStats>,
     duration_ms: f64,
 }
@@ -684,6 +867,8 @@ pub async fn parse_unit_state(
     let per_unit_loop_start = Instant::now();
     let mut state_dbus_fetches: u64 = 0;
     let mut service_dbus_fetches: u64 = 0;
+    let mut service_cgroup_reads: u64 = 0;
+    let mut get_all_timings = GetAllTimings::default();
     let mut timer_dbus_fetches: u64 = 0;
 
     // Cheap synchronous unit-type/state counting first, separate from the
@@ -707,10 +892,15 @@ pub async fn parse_unit_state(
     // attaching this as the explicit parent below, every `unit_collect` span
     // becomes its own unrelated root trace instead of a child of this collector.
     let parent_span = tracing::Span::current();
+    let cgroup_root = Arc::new(PathBuf::from(format!(
+        "{fs_root}{}",
+        crate::cgroup::CGROUP_ROOT
+    )));
     let mut join_set: JoinSet<PerUnitOutcome> = JoinSet::new();
     for unit in listed_units {
         let semaphore = Arc::clone(&semaphore);
         let config = Arc::clone(config);
+        let cgroup_root = Arc::clone(&cgroup_root);
         let connection = connection.clone();
         let parent_span = parent_span.clone();
         join_set.spawn(async move {
@@ -746,8 +936,25 @@ pub async fn parse_unit_state(
                 // Collect service stats
                 if config.services.contains(&unit.name) {
                     debug!("Collecting service stats for {:?}", &unit);
-                    match parse_service(&connection, &unit.name, &unit.unit_object_path).await {
-                        Ok(service_stats) => outcome.service_stats_entry = Some(service_stats),
+                    match parse_service(
+                        &connection,
+                        &unit.name,
+                        &unit.unit_object_path,
+                        &cgroup_root,
+                        config.units.cgroup_fast_path,
+                        &mut outcome.get_all_timings,
+                    )
+                    .await
+                    {
+                        Ok(mut parsed) => {
+                            if let (true, Some(dir)) = (config.pressure.enabled, &parsed.cgroup_dir)
+                            {
+                                parsed.stats.pressure =
+                                    Some(crate::cgroup::read_cgroup_pressure(dir).await);
+                            }
+                            outcome.service_cgroup_read = parsed.cgroup_read;
+                            outcome.service_stats_entry = Some(parsed.stats)
+                        }
                         Err(err) => error!(
                             "Unable to get service stats for {} {}: {:#?}",
                             &unit.name, &unit.unit_object_path, err
@@ -762,7 +969,13 @@ pub async fn parse_unit_state(
                     && (config.timers.allowlist.is_empty()
                         || config.timers.allowlist.contains(&unit.name))
                 {
-                    match crate::timer::collect_timer_stats(&connection, &unit).await {
+                    match crate::timer::collect_timer_stats(
+                        &connection,
+                        &unit,
+                        &mut outcome.get_all_timings,
+                    )
+                    .await
+                    {
                         Ok(ts) => outcome.timer_stats_entry = Some(ts),
                         Err(err) => error!("Failed to get {} stats: {:#?}", &unit.name, err),
                     }
@@ -799,6 +1012,10 @@ pub async fn parse_unit_state(
                 .insert(outcome.unit_name.clone(), service_stats);
             service_dbus_fetches += 1;
         }
+        if outcome.service_cgroup_read {
+            service_cgroup_reads += 1;
+        }
+        get_all_timings.add(&outcome.get_all_timings);
         if let Some(ts) = outcome.timer_stats_entry {
             if ts.persistent {
                 stats.timer_persistent_units += 1;
@@ -824,6 +1041,9 @@ pub async fn parse_unit_state(
     stats.collection_timings.per_unit_loop_ms = per_unit_loop_elapsed.as_secs_f64() * 1000.0;
     stats.collection_timings.state_dbus_fetches = state_dbus_fetches;
     stats.collection_timings.service_dbus_fetches = service_dbus_fetches;
+    stats.collection_timings.service_cgroup_reads = service_cgroup_reads;
+    stats.collection_timings.properties_get_all_calls = get_all_timings.calls;
+    stats.collection_timings.properties_get_all_ms = get_all_timings.elapsed_ms;
     stats.collection_timings.timer_dbus_fetches = timer_dbus_fetches;
 
     debug!("unit stats: {:?}", stats);
@@ -918,6 +1138,7 @@ mod tests {
                     time_in_state_usecs: None,
                 },
             )]),
+            unit_state_transitions: HashMap::new(),
             collection_timings: UnitsCollectionTimings::default(),
         };
         let mut stats = SystemdUnitStats::default();
@@ -993,6 +1214,7 @@ mod tests {
             service_stats: HashMap::new(),
             timer_stats: HashMap::new(),
             unit_states: HashMap::new(),
+            unit_state_transitions: HashMap::new(),
             collection_timings: UnitsCollectionTimings::default(),
         };
         let mut stats = SystemdUnitStats::default();
@@ -1012,6 +1234,20 @@ mod tests {
         assert_eq!(stats.inactive_units, 0);
     }
 
+    #[test]
+    fn test_optional_u64() {
+        assert_eq!(Some(4096), optional_u64(Ok(4096)).expect("value"));
+        assert_eq!(None, optional_u64(Ok(u64::MAX)).expect("accounting off"));
+        let unknown_property = zbus::Error::FDO(Box::new(zbus::fdo::Error::UnknownProperty(
+            String::from("MemoryZSwapCurrent"),
+        )));
+        assert_eq!(
+            None,
+            optional_u64(Err(unknown_property)).expect("older systemd")
+        );
+        assert!(optional_u64(Err(zbus::Error::InvalidReply)).is_err());
+    }
+
     #[test]
     fn test_iterators() {
         assert!(SystemdUnitActiveState::iter().collect::<Vec<_>>().len() > 0);
@@ -1069,4 +1305,115 @@ mod tests {
         assert_eq!(stats.user.transient.get("scope"), Some(&2));
         assert!(stats.user.generated.is_empty());
     }
+
+    #[test]
+    fn test_cgroup_or_dbus() {
+        let dbus_error = || Err(zbus::Error::Unsupported);
+        // The cgroupfs value wins over the D-Bus property
+        assert_eq!(5, cgroup_or_dbus(Some(5), dbus_error()).unwrap());
+        assert!(cgroup_or_dbus(None, dbus_error()).is_err());
+        assert_eq!(7, cgroup_or_dbus(None, Ok(7)).unwrap());
+    }
+
+    fn owned_str(value: &str) -> zbus::zvariant::OwnedValue {
+        zbus::zvariant::OwnedValue::try_from(zbus::zvariant::Value::from(value)).expect("owned str")
+    }
+
+    #[test]
+    fn test_service_stats_from_properties() {
+        use zbus::zvariant::OwnedValue;
+
+        let unit = Properties::new(
+            UNIT_INTERFACE,
+            HashMap::from([
+                (
+                    String::from("ActiveEnterTimestamp"),
+                    OwnedValue::from(100_u64),
+                ),
+                (String::from("ActiveExitTimestamp"), OwnedValue::from(0_u64)),
+                (
+                    String::from("InactiveExitTimestamp"),
+                    OwnedValue::from(90_u64),
+                ),
+                (
+                    String::from("StateChangeTimestamp"),
+                    OwnedValue::from(100_u64),
+                ),
+            ]),
+        );
+        let mut service_values = HashMap::from([
+            (String::from("CPUUsageNSec"), OwnedValue::from(1_000_u64)),
+            (String::from("ExecMainCode"), OwnedValue::from(1_i32)),
+            (String::from("ExecMainPID"), OwnedValue::from(1234_u32)),
+            (String::from("ExecMainStatus"), OwnedValue::from(2_i32)),
+            (String::from("IOReadBytes"), OwnedValue::from(10_u64)),
+            (String::from("IOReadOperations"), OwnedValue::from(1_u64)),
+            (String::from("IOWriteBytes"), OwnedValue::from(20_u64)),
+            (String::from("IOWriteOperations"), OwnedValue::from(2_u64)),
+            (String::from("IPEgressBytes"), OwnedValue::from(u64::MAX)),
+            (String::from("IPEgressPackets"), OwnedValue::from(u64::MAX)),
+            (String::from("IPIngressBytes"), OwnedValue::from(u64::MAX)),
+            (String::from("IPIngressPackets"), OwnedValue::from(u64::MAX)),
+            (String::from("MemoryAvailable"), OwnedValue::from(u64::MAX)),
+            (String::from("MemoryCurrent"), OwnedValue::from(4096_u64)),
+            (String::from("MemorySwapCurrent"), OwnedValue::from(0_u64)),
+            (String::from("MemorySwapPeak"), OwnedValue::from(0_u64)),
+            (String::from("MemoryZSwapCurrent"), OwnedValue::from(0_u64)),
+            (String::from("NRestarts"), OwnedValue::from(3_u32)),
+            (String::from("RestartUSec"), OwnedValue::from(100_000_u64)),
+            (
+                String::from("RestartUSecNext"),
+                OwnedValue::from(200_000_u64),
+            ),
+            (String::from("Result"), owned_str("exit-code")),
+            (String::from("StatusErrno"), OwnedValue::from(0_i32)),
+            (String::from("StatusText"), owned_str("Ready")),
+            (String::from("TasksCurrent"), OwnedValue::from(4_u64)),
+            (String::from("TimeoutCleanUSec"), OwnedValue::from(u64::MAX)),
+            (String::from("WatchdogUSec"), OwnedValue::from(0_u64)),
+        ]);
+        let cgroup_stats = CgroupStats {
+            cpuusage_nsec: Some(5_000),
+            ..Default::default()
+        };
+        let service = Properties::new(SERVICE_INTERFACE, service_values.clone());
+        let stats = service_stats_from_properties(&unit, &service, 2, &cgroup_stats)
+            .expect("decodable properties");
+        // The cgroupfs value wins
+        assert_eq!(5_000, stats.cpuusage_nsec);
+        assert_eq!(4096, stats.memory_current);
+        assert_eq!(1234, stats.exec_main_pid);
+        assert_eq!(3, stats.nrestarts);
+        assert_eq!(2, stats.processes);
+        assert_eq!(Some(200_000), stats.restart_usec_next);
+        assert_eq!(SystemdServiceResult::exit_code, stats.result);
+        assert_eq!("Ready", stats.status_text);
+        // Older systemd without MemoryPeak
+        assert_eq!(None, stats.memory_peak);
+
+        // Properties every supported systemd has are still required
+        service_values.remove("NRestarts");
+        let service = Properties::new(SERVICE_INTERFACE, service_values);
+        assert!(service_stats_from_properties(&unit, &service, 2, &cgroup_stats).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_service_cgroup_stats() {
+        let cgroup_dir = tempfile::tempdir().expect("Unable to create temp dir");
+        // Stopped service: its cgroup is gone
+        assert_eq!(
+            None,
+            read_service_cgroup_stats(&cgroup_dir.path().join("foo.service")).await
+        );
+
+        std::fs::write(cgroup_dir.path().join("cpu.stat"), "usage_usec 10\n").unwrap();
+        std::fs::write(cgroup_dir.path().join("memory.current"), "8192\n").unwrap();
+        let cgroup_stats = read_service_cgroup_stats(cgroup_dir.path())
+            .await
+            .expect("readable cgroup");
+        assert_eq!(Some(10_000), cgroup_stats.cpuusage_nsec);
+        assert_eq!(Some(8192), cgroup_stats.memory_current);
+        // No io controller: fall back to D-Bus for these
+        assert_eq!(None, cgroup_stats.ioread_bytes);
+    }
 }
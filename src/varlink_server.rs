@@ -0,0 +1,369 @@
+//! # varlink_server module
+//!
+//! Serve the latest `MonitordStats` over monitord's own Varlink interface,
+//! `io.monitord.Stats`, on a unix socket in daemon mode. Local tools can fetch
+//! everything (`Get`), a single unit (`GetUnit`) or container (`GetMachine`),
+//! or ask for `Subscribe` with `more` to get the stats after every run.
+//!
+//! ```text
+//! varlinkctl call /run/monitord/io.monitord.Stats io.monitord.Stats.GetUnit '{"name": "sshd.service"}'
+//! varlinkctl call --more /run/monitord/io.monitord.Stats io.monitord.Stats.Subscribe '{}'
+//! ```
+
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::error;
+use zlink::introspect;
+use zlink::notified::{self, traits::State as _};
+
+use crate::timer::TimerStats;
+use crate::units::ServiceStats;
+use crate::units::UnitStates;
+use crate::MachineStats;
+use crate::MonitordStats;
+
+pub const INTERFACE: &str = "io.monitord.Stats";
+
+#[derive(Error, Debug)]
+pub enum MonitordVarlinkServerError {
+    #[error("Varlink server I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Varlink server error: {0}")]
+    ZlinkError(#[from] zlink::Error),
+    #[error("Varlink server thread exited before it started listening")]
+    ServerExited,
+    #[error("{0} exists and is not a stale Varlink socket")]
+    SocketPathInUse(PathBuf),
+}
+
+/// Errors returned to Varlink callers
+#[derive(Debug, Clone, PartialEq, zlink::ReplyError, introspect::ReplyError)]
+#[zlink(interface = "io.monitord.Stats")]
+pub enum StatsError {
+    /// The unit is not in the latest service, timer or unit state stats
+    NoSuchUnit { name: String },
+    /// No stats were collected for the machine in the latest run
+    NoSuchMachine { name: String },
+}
+
+impl std::fmt::Display for StatsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatsError::NoSuchUnit { name } => write!(f, "No stats for unit {}", name),
+            StatsError::NoSuchMachine { name } => write!(f, "No stats for machine {}", name),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+/// Everything collected about one unit. Sections the unit was not collected
+/// for (e.g. `timer` for a service) are null.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct UnitReply {
+    pub name: String,
+    pub state: Option<UnitStates>,
+    pub service: Option<ServiceStats>,
+    pub timer: Option<TimerStats>,
+}
+
+/// `io.monitord.Stats` implementation reading the daemon's shared stats
+pub struct StatsService {
+    stats: Arc<RwLock<MonitordStats>>,
+    updates: notified::State<MonitordStats, MonitordStats>,
+}
+
+#[zlink::service(
+    interface = "io.monitord.Stats",
+    vendor = "monitord",
+    product = "monitord",
+    version = env!("CARGO_PKG_VERSION"),
+    url = "https://github.com/cooperlees/monitord"
+)]
+impl StatsService {
+    /// All stats from the latest collection run
+    async fn get(&self) -> MonitordStats {
+        self.stats.read().await.clone()
+    }
+
+    /// Service, timer and state stats for a single unit
+    async fn get_unit(&self, name: String) -> Result<UnitReply, StatsError> {
+        let stats = self.stats.read().await;
+        let reply = UnitReply {
+            state: stats.units.unit_states.get(&name).cloned(),
+            service: stats.units.service_stats.get(&name).cloned(),
+            timer: stats.units.timer_stats.get(&name).cloned(),
+            name,
+        };
+        if reply.state.is_none() && reply.service.is_none() && reply.timer.is_none() {
+            return Err(StatsError::NoSuchUnit { name: reply.name });
+        }
+        Ok(reply)
+    }
+
+    /// Stats for a single systemd-machined container or VM
+    async fn get_machine(&self, name: String) -> Result<MachineStats, StatsError> {
+        self.stats
+            .read()
+            .await
+            .machines
+            .get(&name)
+            .cloned()
+            .ok_or(StatsError::NoSuchMachine { name })
+    }
+
+    /// Latest stats, or with `more` the stats after every following run
+    #[zlink(more)]
+    async fn subscribe(&self, more: bool) -> notified::Stream<MonitordStats> {
+        if more {
+            self.updates.stream()
+        } else {
+            notified::State::new(self.stats.read().await.clone()).stream_once()
+        }
+    }
+}
+
+/// Handle used by the collection loop to notify `Subscribe` callers
+pub struct StatsPublisher {
+    updates: notified::State<MonitordStats, MonitordStats>,
+}
+
+impl StatsPublisher {
+    /// Send the stats from a finished collection run to all subscribers
+    pub async fn publish(&mut self, stats: &MonitordStats) {
+        self.updates.set(stats.clone()).await;
+    }
+}
+
+/// Bind the socket, replacing a stale one left by a previous run. Anything
+/// else at `socket_path` (a regular file or a socket something is still
+/// listening on) is left alone and is an error.
+fn bind_socket(socket_path: &Path) -> Result<zlink::unix::Listener, MonitordVarlinkServerError> {
+    if let Some(parent) = socket_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match std::fs::symlink_metadata(socket_path) {
+        Ok(metadata) => {
+            let stale = metadata.file_type().is_socket()
+                && matches!(
+                    std::os::unix::net::UnixStream::connect(socket_path),
+                    Err(err) if err.kind() == std::io::ErrorKind::ConnectionRefused
+                );
+            if !stale {
+                return Err(MonitordVarlinkServerError::SocketPathInUse(
+                    socket_path.to_path_buf(),
+                ));
+            }
+            std::fs::remove_file(socket_path)?;
+        }
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+        Err(_) => (),
+    }
+    Ok(zlink::unix::bind(socket_path)?)
+}
+
+/// Serve `io.monitord.Stats` on `socket_path` until the process exits.
+///
+/// zlink's server future is not `Send` so it runs on a dedicated thread with
+/// its own current thread runtime. Returns once the socket is listening.
+pub async fn serve(
+    socket_path: PathBuf,
+    stats: Arc<RwLock<MonitordStats>>,
+) -> Result<StatsPublisher, MonitordVarlinkServerError> {
+    let updates = notified::State::new(MonitordStats::default());
+    let service = StatsService {
+        stats,
+        updates: updates.clone(),
+    };
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+    std::thread::Builder::new()
+        .name(String::from("varlink-server"))
+        .spawn(move || {
+            let rt = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt,
+                Err(err) => {
+                    let _ = ready_tx.send(Err(err.into()));
+                    return;
+                }
+            };
+            rt.block_on(async move {
+                let listener = match bind_socket(&socket_path) {
+                    Ok(listener) => listener,
+                    Err(err) => {
+                        let _ = ready_tx.send(Err(err));
+                        return;
+                    }
+                };
+                let _ = ready_tx.send(Ok(()));
+                if let Err(err) = zlink::Server::new(listener, service).run().await {
+                    error!(
+                        "Varlink server on {} failed: {:?}",
+                        socket_path.display(),
+                        err
+                    );
+                }
+            });
+        })?;
+    ready_rx
+        .await
+        .map_err(|_| MonitordVarlinkServerError::ServerExited)??;
+    Ok(StatsPublisher { updates })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream::TryStreamExt;
+
+    use super::*;
+
+    #[zlink::proxy("io.monitord.Stats")]
+    trait StatsProxy {
+        async fn get(&mut self) -> zlink::Result<Result<MonitordStats, StatsError>>;
+        async fn get_unit(&mut self, name: &str) -> zlink::Result<Result<UnitReply, StatsError>>;
+        async fn get_machine(
+            &mut self,
+            name: &str,
+        ) -> zlink::Result<Result<MachineStats, StatsError>>;
+        #[zlink(more)]
+        async fn subscribe(
+            &mut self,
+        ) -> zlink::Result<
+            impl futures_util::Stream<Item = zlink::Result<Result<MonitordStats, StatsError>>>,
+        >;
+    }
+
+    fn return_monitord_stats() -> MonitordStats {
+        let mut stats = MonitordStats {
+            stat_collection_run_time_ms: 69.0,
+            ..Default::default()
+        };
+        stats.units.service_stats.insert(
+            String::from("foo.service"),
+            ServiceStats {
+                nrestarts: 1,
+                ..Default::default()
+            },
+        );
+        stats.units.unit_states.insert(
+            String::from("foo.service"),
+            UnitStates {
+                unhealthy: true,
+                ..Default::default()
+            },
+        );
+        stats
+            .machines
+            .insert(String::from("foo"), MachineStats::default());
+        stats
+    }
+
+    #[tokio::test]
+    async fn test_varlink_server() {
+        let dir = tempfile::tempdir().expect("Unable to make temp dir");
+        let socket_path = dir.path().join("io.monitord.Stats");
+        let stats = Arc::new(RwLock::new(return_monitord_stats()));
+        let mut publisher = serve(socket_path.clone(), stats.clone())
+            .await
+            .expect("Unable to start varlink server");
+
+        let mut conn = zlink::unix::connect(&socket_path)
+            .await
+            .expect("Unable to connect");
+        assert_eq!(
+            return_monitord_stats(),
+            conn.get().await.expect("Get failed").expect("Get error")
+        );
+
+        let unit = conn
+            .get_unit("foo.service")
+            .await
+            .expect("GetUnit failed")
+            .expect("GetUnit error");
+        assert_eq!(1, unit.service.expect("No service stats").nrestarts);
+        assert!(unit.state.expect("No unit state").unhealthy);
+        assert!(unit.timer.is_none());
+        assert_eq!(
+            StatsError::NoSuchUnit {
+                name: String::from("bar.service")
+            },
+            conn.get_unit("bar.service")
+                .await
+                .expect("GetUnit failed")
+                .expect_err("GetUnit of unknown unit worked")
+        );
+
+        assert_eq!(
+            MachineStats::default(),
+            conn.get_machine("foo")
+                .await
+                .expect("GetMachine failed")
+                .expect("GetMachine error")
+        );
+        assert!(conn
+            .get_machine("bar")
+            .await
+            .expect("GetMachine failed")
+            .is_err());
+
+        let mut sub_conn = zlink::unix::connect(&socket_path)
+            .await
+            .expect("Unable to connect");
+        let updates = sub_conn.subscribe().await.expect("Subscribe failed");
+        futures_util::pin_mut!(updates);
+        let new_stats = MonitordStats {
+            stat_collection_run_time_ms: 420.0,
+            ..Default::default()
+        };
+        // Updates published before the server registers the subscription are
+        // not delivered, so keep publishing until one arrives
+        let publish_stats = new_stats.clone();
+        let publish_task = tokio::spawn(async move {
+            loop {
+                publisher.publish(&publish_stats).await;
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        });
+        let update = tokio::time::timeout(std::time::Duration::from_secs(30), updates.try_next())
+            .await
+            .expect("No update within 30s")
+            .expect("Subscribe stream failed")
+            .expect("Subscribe stream ended")
+            .expect("Subscribe error");
+        publish_task.abort();
+        assert_eq!(new_stats, update);
+    }
+
+    #[tokio::test]
+    async fn test_bind_socket() {
+        let temp_dir = tempfile::tempdir().expect("Unable to create temp dir");
+
+        let file_path = temp_dir.path().join("not-a-socket");
+        std::fs::write(&file_path, "keep me").unwrap();
+        assert!(matches!(
+            bind_socket(&file_path),
+            Err(MonitordVarlinkServerError::SocketPathInUse(_))
+        ));
+        assert_eq!("keep me", std::fs::read_to_string(&file_path).unwrap());
+
+        // Left behind by a previous run that didn't clean up
+        let socket_path = temp_dir.path().join("io.monitord.Stats");
+        drop(std::os::unix::net::UnixListener::bind(&socket_path).unwrap());
+        let _listener = bind_socket(&socket_path).expect("stale socket not replaced");
+
+        // Now in use by _listener
+        assert!(matches!(
+            bind_socket(&socket_path),
+            Err(MonitordVarlinkServerError::SocketPathInUse(_))
+        ));
+    }
+}
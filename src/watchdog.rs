@@ -0,0 +1,85 @@
+//! # watchdog module
+//!
+//! PID 1's hardware watchdog settings and when it last pinged the device, so
+//! hosts with the watchdog disabled or late pings can be alerted on.
+//! Per-service watchdogs are reported with the service stats in `units`.
+
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use tokio::sync::RwLock;
+
+use crate::dbus::zbus_systemd::ManagerProxy;
+use crate::MachineStats;
+
+/// systemd manager watchdog properties. A `*_watchdog_usec` of 0 means that
+/// watchdog is off.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct WatchdogStats {
+    /// RuntimeWatchdogSec=: how often PID 1 has to ping the hardware watchdog
+    pub runtime_watchdog_usec: u64,
+    /// RebootWatchdogSec=: watchdog timeout while rebooting
+    pub reboot_watchdog_usec: u64,
+    /// KExecWatchdogSec=: watchdog timeout while kexec'ing
+    pub kexec_watchdog_usec: u64,
+    /// Watchdog device, e.g. /dev/watchdog0. Empty when none is open.
+    pub device: String,
+    /// CLOCK_MONOTONIC time of the last ping. 0 if never pinged.
+    pub last_ping_timestamp_monotonic: u64,
+    /// Time since the last ping, None if never pinged. Uses the realtime
+    /// `WatchdogLastPingTimestamp` as we can't read CLOCK_MONOTONIC here.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_ping_age_usec: Option<u64>,
+}
+
+/// Age of a realtime usec timestamp at `now_usec`
+fn ping_age_usec(last_ping_usec: u64, now_usec: u64) -> Option<u64> {
+    match last_ping_usec {
+        0 => None,
+        _ => Some(now_usec.saturating_sub(last_ping_usec)),
+    }
+}
+
+pub async fn get_watchdog_stats(connection: &zbus::Connection) -> anyhow::Result<WatchdogStats> {
+    let manager = ManagerProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    let last_ping_usec = manager.watchdog_last_ping_timestamp().await?;
+    let now_usec = u64::try_from(SystemTime::now().duration_since(UNIX_EPOCH)?.as_micros())?;
+    Ok(WatchdogStats {
+        runtime_watchdog_usec: manager.runtime_watchdog_usec().await?,
+        reboot_watchdog_usec: manager.reboot_watchdog_usec().await?,
+        kexec_watchdog_usec: manager.kexec_watchdog_usec().await?,
+        device: manager.watchdog_device().await?,
+        last_ping_timestamp_monotonic: manager.watchdog_last_ping_timestamp_monotonic().await?,
+        last_ping_age_usec: ping_age_usec(last_ping_usec, now_usec),
+    })
+}
+
+/// Async wrapper than can update watchdog stats when passed a locked struct
+pub async fn update_watchdog_stats(
+    connection: zbus::Connection,
+    locked_machine_stats: Arc<RwLock<MachineStats>>,
+) -> anyhow::Result<()> {
+    let watchdog_stats = get_watchdog_stats(&connection)
+        .await
+        .map_err(|e| anyhow::anyhow!("Error getting watchdog stats: {:?}", e))?;
+    let mut machine_stats = locked_machine_stats.write().await;
+    machine_stats.watchdog = Some(watchdog_stats);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ping_age_usec() {
+        assert_eq!(None, ping_age_usec(0, 1_000_000));
+        assert_eq!(Some(250_000), ping_age_usec(750_000, 1_000_000));
+        // Clock stepped backwards since the ping
+        assert_eq!(Some(0), ping_age_usec(2_000_000, 1_000_000));
+    }
+}